@@ -2,17 +2,38 @@
 #![warn(clippy::pedantic)]
 
 use std::{
-    collections::{binary_heap, HashMap},
+    cell::{Cell, RefCell},
+    collections::{binary_heap, BTreeSet, HashMap},
+    ops::RangeInclusive,
     rc::Rc,
 };
 
 use color_eyre::{eyre::Error, owo_colors::OwoColorize};
+use ratatui::layout::Rect;
 use rtwlib::{
-    camera::{GradientSky, Sky},
+    camera::{Camera, GradientSky, Sky},
     color::Color,
-    hittable::{plane::Plane, sphere::Sphere, Hittable, HittableList},
+    hittable::{plane::Plane, sphere::Sphere, HitRecord, Hittable, HittableList},
     material::{Dielectric, Lambertian, Material, Metal, Normal},
-    vec3::{Point3, Vec3},
+    ray::Ray,
+    vec3::{dot, Point3, Vec3},
+};
+
+use crate::{
+    array::{self, ArrayPattern, ArraySettings},
+    form::{self, hex_bytes, Field, FieldKind},
+    graphics::GraphicsOutput,
+    group::{self, is_within, SceneGroup, TableRow},
+    history::{History, Snapshot},
+    input::TextInput,
+    keys::Keymap,
+    picker::{ColorPicker, RECENT_COLORS},
+    preview::{PreviewCache, PreviewMode, PreviewQuality, Shading},
+    projection::{Projection, Projector},
+    render::RenderStats,
+    theme::Theme,
+    viewer::Viewer,
+    worker::PreviewWorker,
 };
 
 pub enum CurrentScreen {
@@ -24,8 +45,14 @@ pub enum CurrentScreen {
     Preview,
     PreviewFull,
     SkyEditor,
+    Viewer,
+    ArrayEditor,
+    Rename,
+    BulkEditor,
+    GroupEditor,
 }
 
+#[derive(Clone, Copy, PartialEq)]
 pub enum CurrentlyEditing {
     Type,
     Size,
@@ -33,13 +60,18 @@ pub enum CurrentlyEditing {
     PositionY,
     PositionZ,
     Material,
+    ObjectName,
+    Rename,
     MatType,
     MatColor,
     MatProperty,
     MatName,
     // Render
+    Preset,
     Height,
     Width,
+    AspectLock,
+    Scale,
     ImgName,
     Samples,
     Bounces,
@@ -52,21 +84,216 @@ pub enum CurrentlyEditing {
     Fov,
     FocusDist,
     Aperture,
+    Projection,
+    OrthoWidth,
     SkyColor1,
     SkyColor2,
     SkyType,
+    // Array
+    ArrayPattern,
+    ArrayCount,
+    ArrayRows,
+    ArrayX,
+    ArrayY,
+    ArrayZ,
+    ArrayMaterials,
+    // Bulk
+    BulkX,
+    BulkY,
+    BulkZ,
+    BulkScale,
+    BulkMaterial,
+    // Group
+    GroupName,
+    GroupX,
+    GroupY,
+    GroupZ,
+    GroupScale,
+    GroupTurn,
+    GroupPivotX,
+    GroupPivotY,
+    GroupPivotZ,
 }
-#[derive(Clone)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
 pub enum MaterialType {
     Lambertian,
     Metal,
     Dielectric,
     Normal,
 }
+#[derive(Clone, Copy, PartialEq)]
 pub enum SkyType {
     Solid,
     Gradient,
 }
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ObjectType {
+    Sphere,
+    Plane,
+}
+
+impl std::fmt::Display for ObjectType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ObjectType::Sphere => write!(f, "Sphere"),
+            ObjectType::Plane => write!(f, "Plane"),
+        }
+    }
+}
+
+/// An object in the scene. The library objects don't expose their settings once built, so the app
+/// keeps its own copy and builds the `HittableList` from these when it's needed.
+#[derive(Clone)]
+pub struct SceneObject {
+    pub kind: ObjectType,
+    /// Radius for spheres, height for planes
+    pub size: f64,
+    /// Center for spheres, facing direction for planes
+    pub position: Point3,
+    /// Index into `App::materials`
+    pub material: usize,
+    /// Shown in the table, empty for the default like "Sphere 3"
+    pub name: String,
+    /// Left out of the preview and render, but kept in the scene
+    pub hidden: bool,
+    /// The only object in the preview and render, hidden or not
+    pub solo: bool,
+    /// The group the object is in, an index into `App::groups`. Its position and size are
+    /// relative to the group
+    pub group: Option<usize>,
+}
+
+impl SceneObject {
+    /// The object's name, or its type and `index` if it hasn't been given one.
+    pub fn label(&self, index: usize) -> String {
+        if self.name.is_empty() {
+            format!("{} {index}", self.kind)
+        } else {
+            self.name.clone()
+        }
+    }
+
+    pub fn to_hittable(&self, materials: &[SceneMaterial]) -> Box<dyn Hittable> {
+        let mat = materials[self.material].to_material();
+        match self.kind {
+            ObjectType::Sphere => Box::new(Sphere::new(self.position, self.size, mat)),
+            ObjectType::Plane => Box::new(Plane::new(
+                Point3::new(0., self.size, 0.),
+                self.position,
+                mat,
+            )),
+        }
+    }
+
+    /// A copy moved by `offset`. Planes are stored by where they cross the Y axis, so one standing
+    /// straight up can't be moved sideways and is copied in place.
+    pub fn translated(&self, offset: Vec3) -> SceneObject {
+        let mut copy = self.clone();
+        match self.kind {
+            ObjectType::Sphere => copy.position = self.position + offset,
+            ObjectType::Plane => {
+                let through = Point3::new(0., self.size, 0.) + offset;
+                copy.size = plane_height(self.position, through).unwrap_or(self.size);
+            }
+        }
+        copy
+    }
+
+    /// A copy turned by `angle` radians around the vertical line through `center`.
+    pub fn rotated_y(&self, center: Point3, angle: f64) -> SceneObject {
+        let (sin, cos) = angle.sin_cos();
+        let turn = |v: Vec3| Vec3::new(v.x * cos + v.z * sin, v.y, v.z * cos - v.x * sin);
+        let mut copy = self.clone();
+        match self.kind {
+            ObjectType::Sphere => copy.position = center + turn(self.position - center),
+            ObjectType::Plane => {
+                let through = center + turn(Point3::new(0., self.size, 0.) - center);
+                copy.position = turn(self.position);
+                copy.size = plane_height(copy.position, through).unwrap_or(self.size);
+            }
+        }
+        copy
+    }
+
+    /// Distance from `from` to the surface of the object, or `None` if `from` is inside it.
+    /// Planes are measured along `direction` when it faces them, and straight on otherwise.
+    pub fn surface_distance(&self, from: Point3, direction: Vec3) -> Option<f64> {
+        match self.kind {
+            ObjectType::Sphere => {
+                let distance = (self.position - from).length() - self.size;
+                (distance > 0.0).then_some(distance)
+            }
+            ObjectType::Plane => {
+                let normal = self.position.normalized();
+                let offset = dot(&(Point3::new(0., self.size, 0.) - from), &normal);
+                let facing = dot(&direction.normalized(), &normal);
+                if facing.abs() > 1e-4 && offset / facing > 0.0 {
+                    Some(offset / facing)
+                } else {
+                    (offset.abs() > 0.0).then_some(offset.abs())
+                }
+            }
+        }
+    }
+}
+/// Indices of the objects that go in the preview and render. While an object is soloed it's the
+/// only one, otherwise it's every object that isn't hidden.
+pub fn shown(objects: &[SceneObject]) -> Vec<usize> {
+    let solo = objects.iter().any(|object| object.solo);
+    (0..objects.len())
+        .filter(|&i| {
+            if solo {
+                objects[i].solo
+            } else {
+                !objects[i].hidden
+            }
+        })
+        .collect()
+}
+
+/// Where the plane facing `normal` through `point` crosses the Y axis, if it does.
+pub fn plane_height(normal: Vec3, point: Point3) -> Option<f64> {
+    (normal.y.abs() > 1e-9).then(|| dot(&normal, &point) / normal.y)
+}
+
+/// A material in the scene, kept as plain settings like [`SceneObject`] so it can be shown, hashed
+/// and sent to other threads. The library material is built from it when the world is.
+#[derive(Clone)]
+pub struct SceneMaterial {
+    pub name: String,
+    pub kind: MaterialType,
+    pub color: Color,
+    /// Fuzz for metals, index of refraction for glass
+    pub other: f64,
+}
+
+impl SceneMaterial {
+    pub fn to_material(&self) -> Rc<dyn Material> {
+        match self.kind {
+            MaterialType::Lambertian => Rc::new(Lambertian::new(self.color)),
+            MaterialType::Metal => Rc::new(Metal::new(self.color, self.other)),
+            MaterialType::Normal => Rc::new(Normal::new()),
+            MaterialType::Dielectric => Rc::new(Dielectric::new(self.other)),
+        }
+    }
+}
+
+/// The background, as settings for the same reasons as [`SceneMaterial`].
+#[derive(Clone, Copy)]
+pub enum SceneSky {
+    Solid(Color),
+    Gradient { start: Color, end: Color },
+}
+
+impl SceneSky {
+    pub fn to_sky(self) -> Box<dyn Sky> {
+        match self {
+            SceneSky::Solid(color) => Box::new(color),
+            SceneSky::Gradient { start, end } => Box::new(GradientSky { start, end }),
+        }
+    }
+}
+
 impl std::fmt::Display for MaterialType {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -78,41 +305,161 @@ impl std::fmt::Display for MaterialType {
     }
 }
 
+/// The camera inputs, parsed. Unlike a `Camera` this can be sent to other threads.
+#[derive(Clone)]
+pub struct CameraSettings {
+    pub lookfrom: Point3,
+    pub lookat: Point3,
+    pub vfov: f64,
+    pub focus_dist: f64,
+    pub defocus_angle: f64,
+    pub sky: SceneSky,
+    pub projection: Projection,
+    pub ortho_width: f64,
+}
+
+impl CameraSettings {
+    /// Sets up a camera. Image size and quality are left for the caller to set.
+    pub fn camera(&self) -> Camera {
+        let mut cam = Camera::new();
+        cam.lookfrom = self.lookfrom;
+        cam.lookat = self.lookat;
+
+        // looking straight up or down leaves no way to work out which way is right, so -Z becomes up
+        let direction = (cam.lookat - cam.lookfrom).normalized();
+        cam.vup = if direction.x.abs() < 1e-6 && direction.z.abs() < 1e-6 {
+            Point3::new(0.0, 0.0, -1.0)
+        } else {
+            Point3::new(0.0, 1.0, 0.0)
+        };
+
+        cam.vfov = self.vfov;
+        cam.focus_dist = self.focus_dist;
+        cam.defocus_angle = self.defocus_angle;
+
+        cam.sky = self.sky.to_sky();
+        cam
+    }
+
+    /// Wraps a finished camera with the projection.
+    pub fn projector(&self, cam: Camera) -> Projector {
+        Projector::new(cam, self.projection, self.ortho_width)
+    }
+}
+
+/// Range for positions and sizes in the scene
+const COORDINATE_RANGE: RangeInclusive<f64> = -1000.0..=1000.0;
+
+/// Common image sizes, picked from on the render screen. (name, width, height)
+pub const RESOLUTION_PRESETS: [(&str, u32, u32); 5] = [
+    ("720p", 1280, 720),
+    ("1080p", 1920, 1080),
+    ("Square", 1080, 1080),
+    ("4K", 3840, 2160),
+    ("Thumbnail", 320, 180),
+];
+
 pub struct App {
     pub current_screen: CurrentScreen,
     pub current_edit: Option<CurrentlyEditing>,
-    pub world: HittableList,
-    pub materials: Vec<(String, Rc<dyn Material>)>,
+    pub objects: Vec<SceneObject>,
+    pub groups: Vec<SceneGroup>,
+    pub materials: Vec<SceneMaterial>,
     pub material_input: usize,
     pub type_input: usize,
-    pub size_input: String,
-    pub position_input_x: String,
-    pub position_input_y: String,
-    pub position_input_z: String,
+    pub size_input: TextInput,
+    pub position_input_x: TextInput,
+    pub position_input_y: TextInput,
+    pub position_input_z: TextInput,
+    pub object_name_input: TextInput,
+    /// The new name on the rename screen
+    pub rename_input: TextInput,
     pub mat_type_input: Option<MaterialType>,
-    pub mat_color_input: String,
-    pub mat_other_input: String,
-    pub mat_name_input: String,
-    pub image_name_input: String,
-    pub image_height: String,
-    pub image_width: String,
-    pub samples: String,
-    pub bounces: String,
-    pub camx: String,
-    pub camy: String,
-    pub camz: String,
-    pub lookx: String,
-    pub looky: String,
-    pub lookz: String,
-    pub fov: String,
-    pub focus_dist: String,
-    pub aperture: String,
+    pub mat_color_input: TextInput,
+    pub mat_other_input: TextInput,
+    pub mat_name_input: TextInput,
+    pub image_name_input: TextInput,
+    pub image_height: TextInput,
+    pub image_width: TextInput,
+    /// Width / height to keep while the width is edited, `None` when unlocked
+    pub aspect_lock: Option<f64>,
+    /// Percentage the image size is scaled by when rendering
+    pub render_scale: TextInput,
+    pub samples: TextInput,
+    pub bounces: TextInput,
+    pub camx: TextInput,
+    pub camy: TextInput,
+    pub camz: TextInput,
+    pub lookx: TextInput,
+    pub looky: TextInput,
+    pub lookz: TextInput,
+    pub fov: TextInput,
+    pub focus_dist: TextInput,
+    pub aperture: TextInput,
+    pub projection: Projection,
+    pub ortho_width: TextInput,
     pub render_progress: f64,
+    pub render_stats: Option<RenderStats>,
+    pub viewer: Option<Viewer>,
+    pub preview_mode: PreviewMode,
+    pub shading: Shading,
+    pub preview_quality: PreviewQuality,
+    pub preview_cache: RefCell<Option<PreviewCache>>,
+    pub preview_worker: RefCell<PreviewWorker>,
+    /// Keep the preview up next to the table and editors
+    pub split_preview: bool,
+    /// Show the top-down scene map in the sidebar instead of the material list
+    pub show_map: bool,
+    /// Images the preview wants written to the terminal once the frame is drawn
+    pub graphics: RefCell<GraphicsOutput>,
+    /// Where the last draw put the preview image and the object table, for mouse clicks
+    pub preview_area: Cell<Rect>,
+    pub table_area: Cell<Rect>,
+    /// First object row the table had scrolled to on the last draw
+    pub table_offset: Cell<usize>,
+    /// The row the cursor is on, an object or a group but never both
     pub selected_object: Option<usize>,
+    pub selected_group: Option<usize>,
+    /// Rows picked out for the bulk changes, which use the cursor row while this is empty
+    pub marked: BTreeSet<usize>,
     pub sky_type: SkyType,
-    pub sky_color1: String,
-    pub sky_color2: String,
-    pub sky: Box<dyn Sky>,
+    pub sky_color1: TextInput,
+    pub sky_color2: TextInput,
+    pub sky: SceneSky,
+    pub keys: Keymap,
+    pub theme: Theme,
+    /// The color picker, open over a color field
+    pub picker: Option<ColorPicker>,
+    /// Colors chosen in the picker, newest first
+    pub recent_colors: Vec<[u8; 3]>,
+    pub history: History,
+    pub array_pattern: ArrayPattern,
+    pub array_count: TextInput,
+    pub array_rows: TextInput,
+    /// Offset, spacing or center depending on the pattern, and the offset for duplicates
+    pub array_x: TextInput,
+    pub array_y: TextInput,
+    pub array_z: TextInput,
+    pub array_cycle_materials: bool,
+    /// Offset to move the objects by in the bulk editor
+    pub bulk_x: TextInput,
+    pub bulk_y: TextInput,
+    pub bulk_z: TextInput,
+    /// Factor to multiply the radius of spheres by
+    pub bulk_scale: TextInput,
+    /// Material to give the objects, `None` keeps their own
+    pub bulk_material: Option<usize>,
+    pub group_name: TextInput,
+    /// Offset to move the group's pivot by
+    pub group_x: TextInput,
+    pub group_y: TextInput,
+    pub group_z: TextInput,
+    pub group_scale: TextInput,
+    /// Degrees around the pivot
+    pub group_turn: TextInput,
+    pub group_pivot_x: TextInput,
+    pub group_pivot_y: TextInput,
+    pub group_pivot_z: TextInput,
 }
 
 impl App {
@@ -127,76 +474,303 @@ impl App {
         App {
             current_screen: CurrentScreen::Main,
             current_edit: None,
-            world: HittableList {
-                objects: Vec::new(),
-            },
-            materials: vec![(
-                "Diffuse 1".to_string(),
-                Rc::new(Lambertian::new(Color::from(0.8))),
-            )],
+            objects: Vec::new(),
+            groups: Vec::new(),
+            materials: vec![SceneMaterial {
+                name: "Diffuse 1".to_string(),
+                kind: MaterialType::Lambertian,
+                color: Color::from(0.8),
+                other: 0.0,
+            }],
             material_input: 0,
             type_input: 0,
-            size_input: String::from("0.5"),
-            position_input_x: String::from("0.0"),
-            position_input_y: String::from("0.0"),
-            position_input_z: String::from("0.0"),
+            size_input: TextInput::number("0.5", COORDINATE_RANGE, 0.1),
+            position_input_x: TextInput::number("0.0", COORDINATE_RANGE, 0.1),
+            position_input_y: TextInput::number("0.0", COORDINATE_RANGE, 0.1),
+            position_input_z: TextInput::number("0.0", COORDINATE_RANGE, 0.1),
+            object_name_input: TextInput::from(""),
+            rename_input: TextInput::from(""),
             mat_type_input: None,
-            mat_color_input: String::from("fa4e4e"),
-            mat_other_input: String::from("0.0"),
-            mat_name_input: String::from("Material"),
-            image_height: String::from("600"),
-            image_width: String::from("338"),
-            image_name_input: String::from("image"),
-            samples: String::from("50"),
-            bounces: String::from("15"),
-            camx: String::from("-1.0"),
-            camy: String::from("0.0"),
-            camz: String::from("0.0"),
-            lookx: String::from("0.0"),
-            looky: String::from("0.0"),
-            lookz: String::from("0.0"),
-            fov: String::from("45.0"),
-            focus_dist: String::from("1.5"),
-            aperture: String::from("0.0"),
+            mat_color_input: TextInput::from("fa4e4e"),
+            mat_other_input: TextInput::number("0.0", 0.0..=10.0, 0.1),
+            mat_name_input: TextInput::from("Material"),
+            image_height: TextInput::integer("338", 1..=16384, 10),
+            image_width: TextInput::integer("600", 1..=16384, 10),
+            aspect_lock: None,
+            render_scale: TextInput::integer("100", 1..=400, 10),
+            image_name_input: TextInput::from("image"),
+            samples: TextInput::integer("50", 1..=100_000, 10),
+            bounces: TextInput::integer("15", 1..=1000, 1),
+            camx: TextInput::number("-1.0", COORDINATE_RANGE, 0.1),
+            camy: TextInput::number("0.0", COORDINATE_RANGE, 0.1),
+            camz: TextInput::number("0.0", COORDINATE_RANGE, 0.1),
+            lookx: TextInput::number("0.0", COORDINATE_RANGE, 0.1),
+            looky: TextInput::number("0.0", COORDINATE_RANGE, 0.1),
+            lookz: TextInput::number("0.0", COORDINATE_RANGE, 0.1),
+            fov: TextInput::number("45.0", 1.0..=179.0, 1.0),
+            focus_dist: TextInput::number("1.5", 0.01..=1000.0, 0.1),
+            aperture: TextInput::number("0.0", 0.0..=90.0, 0.1),
+            projection: Projection::Perspective,
+            ortho_width: TextInput::number("4.0", 0.01..=1000.0, 0.1),
             render_progress: 0.0,
+            render_stats: None,
+            viewer: None,
+            // main picks the mode for the terminal, see PreviewMode::detect
+            preview_mode: PreviewMode::HalfBlocks,
+            shading: Shading::Full,
+            preview_quality: PreviewQuality::default(),
+            preview_cache: RefCell::new(None),
+            preview_worker: RefCell::new(PreviewWorker::default()),
+            split_preview: false,
+            show_map: false,
+            graphics: RefCell::new(GraphicsOutput::default()),
+            preview_area: Cell::new(Rect::default()),
+            table_area: Cell::new(Rect::default()),
+            table_offset: Cell::new(0),
             selected_object: None,
-            sky_color1: String::from("a0a0a0"),
-            sky_color2: String::from("ffffff"),
+            selected_group: None,
+            marked: BTreeSet::new(),
+            sky_color1: TextInput::from("a0a0a0"),
+            sky_color2: TextInput::from("ffffff"),
             sky_type: SkyType::Gradient,
-            sky: Box::new(GradientSky {
+            sky: SceneSky::Gradient {
                 start: Color::from_hex("a0a0a0").unwrap(),
                 end: Color::from_hex("ffffff").unwrap(),
-            }),
+            },
+            keys: Keymap::default(),
+            theme: Theme::default(),
+            picker: None,
+            recent_colors: Vec::new(),
+            history: History::default(),
+            array_pattern: ArrayPattern::Line,
+            array_count: TextInput::integer("3", 1..=1000, 1),
+            array_rows: TextInput::integer("3", 1..=1000, 1),
+            array_x: TextInput::number("1.0", COORDINATE_RANGE, 0.1),
+            array_y: TextInput::number("0.0", COORDINATE_RANGE, 0.1),
+            array_z: TextInput::number("0.0", COORDINATE_RANGE, 0.1),
+            array_cycle_materials: false,
+            bulk_x: TextInput::number("0.0", COORDINATE_RANGE, 0.1),
+            bulk_y: TextInput::number("0.0", COORDINATE_RANGE, 0.1),
+            bulk_z: TextInput::number("0.0", COORDINATE_RANGE, 0.1),
+            bulk_scale: TextInput::number("1.0", 0.01..=100.0, 0.1),
+            bulk_material: None,
+            group_name: TextInput::from(""),
+            group_x: TextInput::number("0.0", COORDINATE_RANGE, 0.1),
+            group_y: TextInput::number("0.0", COORDINATE_RANGE, 0.1),
+            group_z: TextInput::number("0.0", COORDINATE_RANGE, 0.1),
+            group_scale: TextInput::number("1.0", 0.01..=100.0, 0.1),
+            group_turn: TextInput::number("0.0", -360.0..=360.0, 5.0),
+            group_pivot_x: TextInput::number("0.0", COORDINATE_RANGE, 0.1),
+            group_pivot_y: TextInput::number("0.0", COORDINATE_RANGE, 0.1),
+            group_pivot_z: TextInput::number("0.0", COORDINATE_RANGE, 0.1),
+        }
+    }
+    /// Builds the scene for rendering from the objects that are shown. Objects in it are in the
+    /// order [`shown`] gives.
+    pub fn world(&self) -> HittableList {
+        HittableList {
+            objects: shown(&self.objects)
+                .into_iter()
+                .map(|i| self.placed(i).to_hittable(&self.materials))
+                .collect(),
+        }
+    }
+
+    /// `self.objects[index]` where it ends up in the scene, after the groups it's in have moved it.
+    pub fn placed(&self, index: usize) -> SceneObject {
+        let mut object = self.objects[index].clone();
+        let mut group = object.group;
+        while let Some(g) = group {
+            object = self.groups[g].transform(&object);
+            group = self.groups[g].parent;
+        }
+        object
+    }
+
+    /// Every object where it ends up in the scene, see [`App::placed`].
+    pub fn placed_objects(&self) -> Vec<SceneObject> {
+        (0..self.objects.len()).map(|i| self.placed(i)).collect()
+    }
+
+    /// Index of the preset matching the current image size, if there is one.
+    pub fn resolution_preset(&self) -> Option<usize> {
+        RESOLUTION_PRESETS.iter().position(|(_, width, height)| {
+            *self.image_width == width.to_string() && *self.image_height == height.to_string()
+        })
+    }
+
+    pub fn cycle_resolution_preset(&mut self, forwards: bool) {
+        let count = RESOLUTION_PRESETS.len();
+        let next = match (self.resolution_preset(), forwards) {
+            (Some(i), true) => (i + 1) % count,
+            (Some(i), false) => (i + count - 1) % count,
+            (None, true) => 0,
+            (None, false) => count - 1,
+        };
+        let (_, width, height) = RESOLUTION_PRESETS[next];
+        self.image_width.set(width.to_string());
+        self.image_height.set(height.to_string());
+        if self.aspect_lock.is_some() {
+            self.aspect_lock = Some(f64::from(width) / f64::from(height));
+        }
+    }
+
+    /// Locks the aspect ratio to the current width and height, or unlocks it.
+    pub fn toggle_aspect_lock(&mut self) {
+        self.aspect_lock = match self.aspect_lock {
+            Some(_) => None,
+            None => match (
+                self.image_width.parse::<f64>(),
+                self.image_height.parse::<f64>(),
+            ) {
+                (Ok(width), Ok(height)) if width > 0.0 && height > 0.0 => Some(width / height),
+                _ => None,
+            },
         }
     }
+
+    /// Recomputes the height from the width when the aspect ratio is locked.
+    pub fn sync_locked_height(&mut self) {
+        if let (Some(aspect), Ok(width)) = (self.aspect_lock, self.image_width.parse::<f64>()) {
+            self.image_height
+                .set(format!("{:.0}", (width / aspect).max(1.0)));
+        }
+    }
+
+    /// The size the image will actually be rendered at, after scaling.
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    pub fn render_size(&self) -> color_eyre::Result<(u32, u32)> {
+        let scale = self.render_scale.parse::<f64>()? / 100.0;
+        let width = (self.image_width.parse::<f64>()? * scale).round().max(1.0) as u32;
+        let height = (self.image_height.parse::<f64>()? * scale).round().max(1.0) as u32;
+        Ok((width, height))
+    }
+
+    /// Opens the current output image in the viewer, returns false if it couldn't be loaded.
+    pub fn open_viewer(&mut self) -> bool {
+        match Viewer::open(format!("{}.ppm", self.image_name_input)) {
+            Ok(viewer) => {
+                self.viewer = Some(viewer);
+                self.current_screen = CurrentScreen::Viewer;
+                true
+            }
+            Err(_) => false,
+        }
+    }
+
+    /// Parses the camera inputs.
+    pub fn camera_settings(&self) -> color_eyre::Result<CameraSettings> {
+        Ok(CameraSettings {
+            lookfrom: Point3::new(
+                self.camx.parse::<f64>()?,
+                self.camy.parse::<f64>()?,
+                self.camz.parse::<f64>()?,
+            ),
+            lookat: Point3::new(
+                self.lookx.parse::<f64>()?,
+                self.looky.parse::<f64>()?,
+                self.lookz.parse::<f64>()?,
+            ),
+            vfov: self.fov.parse::<f64>()?,
+            focus_dist: self.focus_dist.parse::<f64>()?,
+            defocus_angle: self.aperture.parse::<f64>()?,
+            sky: self.sky,
+            projection: self.projection,
+            ortho_width: self.ortho_width.parse::<f64>()?,
+        })
+    }
+
+    /// Sets up a camera from the camera inputs. Image size and quality are left for the caller to set.
+    pub fn camera(&self) -> color_eyre::Result<Camera> {
+        Ok(self.camera_settings()?.camera())
+    }
+
+    /// Wraps a finished camera with the selected projection.
+    pub fn projector(&self, cam: Camera) -> color_eyre::Result<Projector> {
+        Ok(self.camera_settings()?.projector(cam))
+    }
+
+    /// The object the editor would create with its current inputs, if they're valid.
+    pub fn pending_object(&self) -> Option<SceneObject> {
+        Some(SceneObject {
+            kind: match self.type_input {
+                0 => ObjectType::Sphere,
+                _ => ObjectType::Plane,
+            },
+            size: self.size_input.parse().ok()?,
+            position: Point3::new(
+                self.position_input_x.parse::<f64>().ok()?,
+                self.position_input_y.parse::<f64>().ok()?,
+                self.position_input_z.parse::<f64>().ok()?,
+            ),
+            material: self.material_input,
+            name: self.object_name_input.trim().to_string(),
+            hidden: false,
+            solo: false,
+            group: None,
+        })
+    }
+
+    /// Works out a focus distance from the current camera inputs. If an object is selected this is
+    /// the distance to its surface, otherwise it's the first hit along the look-at ray.
+    pub fn auto_focus_distance(&self) -> Option<f64> {
+        let lookfrom = Point3::new(
+            self.camx.parse::<f64>().ok()?,
+            self.camy.parse::<f64>().ok()?,
+            self.camz.parse::<f64>().ok()?,
+        );
+        let lookat = Point3::new(
+            self.lookx.parse::<f64>().ok()?,
+            self.looky.parse::<f64>().ok()?,
+            self.lookz.parse::<f64>().ok()?,
+        );
+        let direction = (lookat - lookfrom).normalized();
+
+        if let Some(selected) = self.selected_object.filter(|&i| i < self.objects.len()) {
+            return self.placed(selected).surface_distance(lookfrom, direction);
+        }
+
+        let mut rec = HitRecord::default();
+        self.world()
+            .hit(&Ray::new(lookfrom, direction), 0.001..f64::INFINITY, &mut rec)
+            .then_some(rec.t)
+    }
+
+    /// Sets the focus distance to [`App::auto_focus_distance`], returns false if nothing was found to focus on.
+    pub fn auto_focus(&mut self) -> bool {
+        match self.auto_focus_distance() {
+            Some(distance) => {
+                self.focus_dist.set(format!("{distance:.3}"));
+                true
+            }
+            None => false,
+        }
+    }
+
     pub fn save_material(&mut self) -> Result<(), String> {
         let other: f64 = self
             .mat_other_input
             .parse()
             .map_err(|_| "Invalid other value")?;
-        let color: Color = self.get_color();
-        let mat: Rc<dyn Material> = match &self.mat_type_input {
-            Some(x) => match x {
-                MaterialType::Lambertian => Rc::new(Lambertian::new(color)),
-                MaterialType::Metal => Rc::new(Metal::new(color, other)),
-                MaterialType::Normal => Rc::new(Normal::new()),
-                MaterialType::Dielectric => Rc::new(Dielectric::new(other)),
-            },
-            None => return Err(String::from("No material type provided")),
-        };
-        self.materials.push((self.mat_name_input.clone(), mat));
-        self.mat_color_input = String::from("fa4e4e");
+        let color = Color::from_hex(&self.mat_color_input).map_err(|_| "Invalid color")?;
+        let kind = self
+            .mat_type_input
+            .ok_or_else(|| String::from("No material type provided"))?;
+        self.materials.push(SceneMaterial {
+            name: self.mat_name_input.to_string(),
+            kind,
+            color,
+            other,
+        });
+        self.mat_color_input.set("fa4e4e");
         self.mat_type_input = None;
-        self.mat_other_input = String::from("1.0");
+        self.mat_other_input.set("1.0");
         Ok(())
     }
     pub fn save_object(&mut self) -> Result<(), String> {
-        let mat: Rc<dyn Material> = self
-            .materials
+        self.materials
             .get(self.material_input)
-            .ok_or("Invalid material input")?
-            .clone()
-            .1;
+            .ok_or("Invalid material input")?;
 
         let size: f64 = self.size_input.parse().map_err(|_| "Invalid size input")?;
 
@@ -217,18 +791,29 @@ impl App {
 
         let position = Point3::new(pos_x, pos_y, pos_z);
 
-        let object: Box<dyn Hittable> = match self.type_input {
-            0 => Box::new(Sphere::new(position, size, mat.clone())),
-            1 => Box::new(Plane::new(Point3::new(0., size, 0.), position, mat.clone())),
+        let kind = match self.type_input {
+            0 => ObjectType::Sphere,
+            1 => ObjectType::Plane,
             _ => return Err(String::from("Invalid object type")),
         };
-        self.world.objects.push(object);
+        self.checkpoint();
+        self.objects.push(SceneObject {
+            kind,
+            size,
+            position,
+            material: self.material_input,
+            name: self.object_name_input.trim().to_string(),
+            hidden: false,
+            solo: false,
+            group: None,
+        });
 
         self.material_input = 0;
-        self.size_input = String::from("0.5");
-        self.position_input_x = String::from("0.0");
-        self.position_input_y = String::from("0.0");
-        self.position_input_z = String::from("0.0");
+        self.object_name_input.set("");
+        self.size_input.set("0.5");
+        self.position_input_x.set("0.0");
+        self.position_input_y.set("0.0");
+        self.position_input_z.set("0.0");
 
         Ok(())
     }
@@ -237,108 +822,694 @@ impl App {
         let end = Color::from_hex(&self.sky_color1)?;
         let start = Color::from_hex(&self.sky_color2)?;
         self.sky = match self.sky_type {
-            SkyType::Solid => Box::new(start),
-            SkyType::Gradient => Box::new(GradientSky { start, end }),
+            SkyType::Solid => SceneSky::Solid(start),
+            SkyType::Gradient => SceneSky::Gradient { start, end },
         };
         Ok(())
     }
 
-    pub fn get_color(&self) -> Color {
-        if str::len(&self.mat_color_input) != 6 {
-            return Color::new(1., 0., 1.);
+    /// The text field being edited, if the focused field takes text.
+    pub fn focused_input(&mut self) -> Option<&mut TextInput> {
+        form::field(self.current_edit?)?.input_mut(self)
+    }
+
+    /// Steps the focused field through its choices. Returns false if it isn't a choice.
+    pub fn cycle_choice(&mut self, forwards: bool) -> bool {
+        match self.current_edit.and_then(form::field) {
+            Some(Field {
+                kind: FieldKind::Choice { cycle, .. },
+                ..
+            }) => {
+                cycle(self, forwards);
+                true
+            }
+            _ => false,
         }
-        let r = u8::from_str_radix(&self.mat_color_input[0..2], 16).unwrap_or_else(|_| 255);
-        let g = u8::from_str_radix(&self.mat_color_input[2..4], 16).unwrap_or_else(|_| 0);
-        let b = u8::from_str_radix(&self.mat_color_input[4..6], 16).unwrap_or_else(|_| 255);
+    }
 
-        Color::new(r as f64 / 255.0, g as f64 / 255.0, b as f64 / 255.0)
+    fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            objects: self.objects.clone(),
+            groups: self.groups.clone(),
+            selected: self.selected_object,
+            selected_group: self.selected_group,
+            marked: self.marked.clone(),
+        }
     }
 
-    pub fn change_editing(&mut self, forwards: bool) {
-        if let Some(edit_mode) = &self.current_edit {
-            self.current_edit = match (edit_mode, forwards) {
-                (CurrentlyEditing::Type, true) => Some(CurrentlyEditing::Size),
-                (CurrentlyEditing::Size, true) => Some(CurrentlyEditing::PositionX),
-                (CurrentlyEditing::PositionX, true) => Some(CurrentlyEditing::PositionY),
-                (CurrentlyEditing::PositionY, true) => Some(CurrentlyEditing::PositionZ),
-                (CurrentlyEditing::PositionZ, true) => Some(CurrentlyEditing::Material),
-                (CurrentlyEditing::Material, true) => Some(CurrentlyEditing::Type),
-
-                (CurrentlyEditing::MatType, true) => Some(CurrentlyEditing::MatColor),
-                (CurrentlyEditing::MatColor, true) => match self.mat_type_input {
-                    Some(MaterialType::Dielectric) | Some(MaterialType::Metal) => {
-                        Some(CurrentlyEditing::MatProperty)
-                    }
-                    _ => Some(CurrentlyEditing::MatName),
-                },
-                (CurrentlyEditing::MatProperty, true) => Some(CurrentlyEditing::MatName),
-                (CurrentlyEditing::MatName, true) => Some(CurrentlyEditing::MatType),
-
-                (CurrentlyEditing::SkyColor1, true) => match self.sky_type {
-                    SkyType::Gradient => Some(CurrentlyEditing::SkyColor2),
-                    SkyType::Solid => Some(CurrentlyEditing::SkyType),
-                },
-                (CurrentlyEditing::SkyColor2, true) => Some(CurrentlyEditing::SkyType),
-                (CurrentlyEditing::SkyType, true) => Some(CurrentlyEditing::SkyColor1),
-
-                (CurrentlyEditing::Width, true) => Some(CurrentlyEditing::Height),
-                (CurrentlyEditing::Height, true) => Some(CurrentlyEditing::ImgName),
-                (CurrentlyEditing::ImgName, true) => Some(CurrentlyEditing::Samples),
-                (CurrentlyEditing::Samples, true) => Some(CurrentlyEditing::Bounces),
-                (CurrentlyEditing::Bounces, true) => Some(CurrentlyEditing::CamX),
-                (CurrentlyEditing::CamX, true) => Some(CurrentlyEditing::CamY),
-                (CurrentlyEditing::CamY, true) => Some(CurrentlyEditing::CamZ),
-                (CurrentlyEditing::CamZ, true) => Some(CurrentlyEditing::LookX),
-                (CurrentlyEditing::LookX, true) => Some(CurrentlyEditing::LookY),
-                (CurrentlyEditing::LookY, true) => Some(CurrentlyEditing::LookZ),
-                (CurrentlyEditing::LookZ, true) => Some(CurrentlyEditing::Fov),
-                (CurrentlyEditing::Fov, true) => Some(CurrentlyEditing::FocusDist),
-                (CurrentlyEditing::FocusDist, true) => Some(CurrentlyEditing::Aperture),
-                (CurrentlyEditing::Aperture, true) => Some(CurrentlyEditing::Width),
-
-                (CurrentlyEditing::Type, false) => Some(CurrentlyEditing::Material),
-                (CurrentlyEditing::Size, false) => Some(CurrentlyEditing::Type),
-                (CurrentlyEditing::PositionX, false) => Some(CurrentlyEditing::Size),
-                (CurrentlyEditing::PositionY, false) => Some(CurrentlyEditing::PositionX),
-                (CurrentlyEditing::PositionZ, false) => Some(CurrentlyEditing::PositionY),
-                (CurrentlyEditing::Material, false) => Some(CurrentlyEditing::PositionZ),
-
-                (CurrentlyEditing::MatType, false) => Some(CurrentlyEditing::MatName),
-                (CurrentlyEditing::MatColor, false) => Some(CurrentlyEditing::MatType),
-                (CurrentlyEditing::MatProperty, false) => Some(CurrentlyEditing::MatColor),
-                (CurrentlyEditing::MatName, false) => match self.mat_type_input {
-                    Some(MaterialType::Dielectric) | Some(MaterialType::Metal) => {
-                        Some(CurrentlyEditing::MatProperty)
-                    }
-                    _ => Some(CurrentlyEditing::MatColor),
-                },
-
-                (CurrentlyEditing::SkyColor1, false) => Some(CurrentlyEditing::SkyType),
-                (CurrentlyEditing::SkyColor2, false) => Some(CurrentlyEditing::SkyColor1),
-                (CurrentlyEditing::SkyType, false) => match self.sky_type {
-                    SkyType::Gradient => Some(CurrentlyEditing::SkyColor2),
-                    SkyType::Solid => Some(CurrentlyEditing::SkyColor1),
-                },
-                (CurrentlyEditing::Width, false) => Some(CurrentlyEditing::Aperture),
-                (CurrentlyEditing::Height, false) => Some(CurrentlyEditing::Width),
-                (CurrentlyEditing::ImgName, false) => Some(CurrentlyEditing::Height),
-                (CurrentlyEditing::Samples, false) => Some(CurrentlyEditing::ImgName),
-                (CurrentlyEditing::Bounces, false) => Some(CurrentlyEditing::Samples),
-                (CurrentlyEditing::CamX, false) => Some(CurrentlyEditing::Bounces),
-                (CurrentlyEditing::CamY, false) => Some(CurrentlyEditing::CamX),
-                (CurrentlyEditing::CamZ, false) => Some(CurrentlyEditing::CamY),
-                (CurrentlyEditing::LookX, false) => Some(CurrentlyEditing::CamZ),
-                (CurrentlyEditing::LookY, false) => Some(CurrentlyEditing::LookX),
-                (CurrentlyEditing::LookZ, false) => Some(CurrentlyEditing::LookY),
-                (CurrentlyEditing::Fov, false) => Some(CurrentlyEditing::LookZ),
-                (CurrentlyEditing::FocusDist, false) => Some(CurrentlyEditing::Fov),
-                (CurrentlyEditing::Aperture, false) => Some(CurrentlyEditing::FocusDist),
+    fn restore(&mut self, snapshot: Snapshot) {
+        self.objects = snapshot.objects;
+        self.groups = snapshot.groups;
+        self.selected_object = snapshot.selected;
+        self.selected_group = snapshot.selected_group;
+        self.marked = snapshot.marked;
+    }
+
+    /// Remembers the objects before a change to them, so it can be undone.
+    pub fn checkpoint(&mut self) {
+        let snapshot = self.snapshot();
+        self.history.push(snapshot);
+    }
+
+    /// Puts the objects back how they were before the last change. Returns false if there's
+    /// nothing to undo.
+    pub fn undo(&mut self) -> bool {
+        let current = self.snapshot();
+        self.history
+            .undo(current)
+            .map(|previous| self.restore(previous))
+            .is_some()
+    }
+
+    /// Makes the last change that was undone again. Returns false if there's nothing to redo.
+    pub fn redo(&mut self) -> bool {
+        let current = self.snapshot();
+        self.history
+            .redo(current)
+            .map(|next| self.restore(next))
+            .is_some()
+    }
+
+    /// The rows of the object table, see [`group::table_rows`].
+    pub fn table_rows(&self) -> Vec<(TableRow, usize)> {
+        group::table_rows(&self.objects, &self.groups)
+    }
+
+    /// The row the cursor is on.
+    pub fn cursor(&self) -> Option<TableRow> {
+        match (self.selected_group, self.selected_object) {
+            (Some(g), _) if g < self.groups.len() => Some(TableRow::Group(g)),
+            (_, Some(i)) if i < self.objects.len() => Some(TableRow::Object(i)),
+            _ => None,
+        }
+    }
+
+    pub fn set_cursor(&mut self, row: Option<TableRow>) {
+        (self.selected_object, self.selected_group) = match row {
+            Some(TableRow::Object(i)) => (Some(i), None),
+            Some(TableRow::Group(g)) => (None, Some(g)),
+            None => (None, None),
+        };
+    }
+
+    /// Where the cursor is in the table, `None` if it's on nothing or inside a collapsed group.
+    pub fn cursor_position(&self) -> Option<usize> {
+        let cursor = self.cursor()?;
+        self.table_rows().iter().position(|&(row, _)| row == cursor)
+    }
+
+    /// Moves the cursor up or down a row, wrapping around at the ends.
+    pub fn move_cursor(&mut self, down: bool) {
+        let rows = self.table_rows();
+        if rows.is_empty() {
+            return;
+        }
+        let next = match (self.cursor_position(), down) {
+            (Some(position), true) => (position + 1) % rows.len(),
+            (None, true) => 0,
+            (Some(0) | None, false) => rows.len() - 1,
+            (Some(position), false) => position - 1,
+        };
+        self.set_cursor(Some(rows[next].0));
+    }
+
+    /// Every object in group `g`, including the ones in groups inside it.
+    pub fn group_members(&self, g: usize) -> Vec<usize> {
+        (0..self.objects.len())
+            .filter(|&i| is_within(&self.groups, self.objects[i].group, g))
+            .collect()
+    }
+
+    /// The objects on a table row, one for an object and everything inside for a group.
+    fn row_objects(&self, row: TableRow) -> Vec<usize> {
+        match row {
+            TableRow::Object(i) => vec![i],
+            TableRow::Group(g) => self.group_members(g),
+        }
+    }
+
+    /// The objects bulk changes apply to, the marked rows or else the cursor row.
+    pub fn targets(&self) -> Vec<usize> {
+        if self.marked.is_empty() {
+            self.cursor()
+                .map(|row| self.row_objects(row))
+                .unwrap_or_default()
+        } else {
+            self.marked
+                .iter()
+                .copied()
+                .filter(|&i| i < self.objects.len())
+                .collect()
+        }
+    }
+
+    /// Marks the cursor row for bulk changes, or unmarks it. A group marks everything in it, or
+    /// unmarks it if it's all marked already.
+    pub fn toggle_marked(&mut self) -> bool {
+        let Some(row) = self.cursor() else {
+            return false;
+        };
+        let objects = self.row_objects(row);
+        if objects.iter().all(|i| self.marked.contains(i)) {
+            for i in objects {
+                self.marked.remove(&i);
             }
         } else {
-            self.current_edit = match self.current_screen {
-                CurrentScreen::MaterialEditor => Some(CurrentlyEditing::MatColor),
-                _ => Some(CurrentlyEditing::Size),
+            self.marked.extend(objects);
+        }
+        true
+    }
+
+    /// Marks the cursor row and the one above or below it, and moves the cursor there. Stops at
+    /// the ends of the table rather than wrapping around.
+    pub fn extend_selection(&mut self, down: bool) -> bool {
+        let rows = self.table_rows();
+        let Some(last) = rows.len().checked_sub(1) else {
+            return false;
+        };
+        let next = match self.cursor_position() {
+            Some(position) => {
+                self.marked.extend(self.row_objects(rows[position].0));
+                if down {
+                    (position + 1).min(last)
+                } else {
+                    position.saturating_sub(1)
+                }
+            }
+            None if down => 0,
+            None => last,
+        };
+        self.marked.extend(self.row_objects(rows[next].0));
+        self.set_cursor(Some(rows[next].0));
+        true
+    }
+
+    /// Marks every object, or clears the marks if they all were.
+    pub fn mark_all(&mut self) {
+        if self.marked.len() == self.objects.len() {
+            self.marked.clear();
+        } else {
+            self.marked = (0..self.objects.len()).collect();
+        }
+    }
+
+    /// Takes objects and groups out of the scene, fixing up the indices of the ones after them.
+    /// Groups that are left shouldn't be in a group that's taken out.
+    fn remove(&mut self, objects: &BTreeSet<usize>, groups: &BTreeSet<usize>) {
+        let new_index = |g: usize| g - groups.range(..g).count();
+        let mut index = 0;
+        self.objects.retain(|_| {
+            index += 1;
+            !objects.contains(&(index - 1))
+        });
+        for object in &mut self.objects {
+            object.group = object.group.filter(|g| !groups.contains(g)).map(new_index);
+        }
+        let mut index = 0;
+        self.groups.retain(|_| {
+            index += 1;
+            !groups.contains(&(index - 1))
+        });
+        for group in &mut self.groups {
+            group.parent = group.parent.filter(|g| !groups.contains(g)).map(new_index);
+        }
+        self.marked.clear();
+    }
+
+    /// Deletes the marked objects, or the row under the cursor, putting the cursor on the row
+    /// that takes the place of the first. A group is deleted with everything in it, and one left
+    /// empty is deleted with its last member.
+    pub fn delete_selected(&mut self) -> bool {
+        let objects: BTreeSet<usize> = self.targets().into_iter().collect();
+        let mut groups: BTreeSet<usize> = match self.cursor() {
+            Some(TableRow::Group(g)) if self.marked.is_empty() => (0..self.groups.len())
+                .filter(|&other| is_within(&self.groups, Some(other), g))
+                .collect(),
+            _ => BTreeSet::new(),
+        };
+        // groups left with nothing in them go too, working up from the deleted objects
+        let mut emptied: Vec<usize> = objects
+            .iter()
+            .filter_map(|&i| self.objects[i].group)
+            .collect();
+        while let Some(g) = emptied.pop() {
+            let kept_object = (0..self.objects.len())
+                .any(|i| self.objects[i].group == Some(g) && !objects.contains(&i));
+            let kept_group = (0..self.groups.len())
+                .any(|other| self.groups[other].parent == Some(g) && !groups.contains(&other));
+            if !kept_object && !kept_group && groups.insert(g) {
+                emptied.extend(self.groups[g].parent);
+            }
+        }
+        let rows = self.table_rows();
+        let Some(first) = rows.iter().position(|&(row, _)| match row {
+            TableRow::Object(i) => objects.contains(&i),
+            TableRow::Group(g) => groups.contains(&g),
+        }) else {
+            return false;
+        };
+        self.checkpoint();
+        self.remove(&objects, &groups);
+        let rows = self.table_rows();
+        let next = rows.get(first.min(rows.len().saturating_sub(1)));
+        self.set_cursor(next.map(|&(row, _)| row));
+        true
+    }
+
+    /// Puts the marked objects, or the row under the cursor, in a new group. If they're all in
+    /// the same group the new one goes inside it, otherwise it's a top level group and they're
+    /// moved out of their groups without changing where they are in the scene.
+    #[allow(clippy::cast_precision_loss)]
+    pub fn group_selected(&mut self) -> bool {
+        let new = self.groups.len();
+        let pivot_of = |objects: &[SceneObject]| {
+            let spheres: Vec<Point3> = objects
+                .iter()
+                .filter(|object| object.kind == ObjectType::Sphere)
+                .map(|object| object.position)
+                .collect();
+            if spheres.is_empty() {
+                Point3::new(0., 0., 0.)
+            } else {
+                spheres
+                    .iter()
+                    .fold(Point3::new(0., 0., 0.), |sum, &position| sum + position)
+                    * (1.0 / spheres.len() as f64)
+            }
+        };
+        match self.cursor() {
+            // a group on its own is wrapped in the new one
+            Some(TableRow::Group(g)) if self.marked.is_empty() => {
+                let members: Vec<SceneObject> = self
+                    .group_members(g)
+                    .into_iter()
+                    .map(|i| self.objects[i].clone())
+                    .collect();
+                let pivot = self.groups[g].transform_point(pivot_of(&members));
+                self.checkpoint();
+                self.groups
+                    .push(SceneGroup::new(String::new(), self.groups[g].parent, pivot));
+                self.groups[g].parent = Some(new);
+            }
+            _ => {
+                let targets = self.targets();
+                let Some(&first) = targets.first() else {
+                    return false;
+                };
+                let parent = self.objects[first].group;
+                let shared = targets.iter().all(|&i| self.objects[i].group == parent);
+                self.checkpoint();
+                for &i in &targets {
+                    if !shared {
+                        self.objects[i] = self.placed(i);
+                    }
+                    self.objects[i].group = Some(new);
+                }
+                let members: Vec<SceneObject> =
+                    targets.iter().map(|&i| self.objects[i].clone()).collect();
+                let parent = if shared { parent } else { None };
+                self.groups
+                    .push(SceneGroup::new(String::new(), parent, pivot_of(&members)));
+            }
+        }
+        self.marked.clear();
+        self.set_cursor(Some(TableRow::Group(new)));
+        true
+    }
+
+    /// The group under the cursor, or the one the object under the cursor is in.
+    fn cursor_group(&self) -> Option<usize> {
+        match self.cursor()? {
+            TableRow::Group(g) => Some(g),
+            TableRow::Object(i) => self.objects[i].group,
+        }
+    }
+
+    /// Takes away the group under the cursor, or the one the object under the cursor is in. What
+    /// was in it moves to the group above, staying where it is in the scene.
+    pub fn ungroup(&mut self) -> bool {
+        let Some(g) = self.cursor_group() else {
+            return false;
+        };
+        self.checkpoint();
+        let group = self.groups[g].clone();
+        for object in &mut self.objects {
+            if object.group == Some(g) {
+                *object = group.transform(object);
+                object.group = group.parent;
+            }
+        }
+        for child in 0..self.groups.len() {
+            if self.groups[child].parent == Some(g) {
+                self.groups[child] = group.absorb(&self.groups[child]);
+            }
+        }
+        let position = self.cursor_position();
+        self.remove(&BTreeSet::new(), &BTreeSet::from([g]));
+        let rows = self.table_rows();
+        let next = position.and_then(|position| rows.get(position).or(rows.last()));
+        self.set_cursor(next.map(|&(row, _)| row));
+        true
+    }
+
+    /// Folds the group under the cursor away in the table, or opens it back up. Collapsing on an
+    /// object collapses the group it's in, and moves the cursor to the group.
+    pub fn set_collapsed(&mut self, collapsed: bool) -> bool {
+        let Some(g) = self.cursor_group() else {
+            return false;
+        };
+        if collapsed || self.cursor() == Some(TableRow::Group(g)) {
+            self.groups[g].collapsed = collapsed;
+        }
+        if collapsed {
+            self.set_cursor(Some(TableRow::Group(g)));
+        }
+        true
+    }
+
+    /// Opens the group editor on the group under the cursor. Returns false if the cursor isn't on
+    /// a group, or objects are marked.
+    pub fn start_group_edit(&mut self) -> bool {
+        let (Some(TableRow::Group(g)), true) = (self.cursor(), self.marked.is_empty()) else {
+            return false;
+        };
+        let group = &self.groups[g];
+        let number = |value: f64| format!("{value:.3}");
+        let values = [
+            number(group.offset.x),
+            number(group.offset.y),
+            number(group.offset.z),
+            number(group.scale),
+            number(group.turn),
+            number(group.pivot.x),
+            number(group.pivot.y),
+            number(group.pivot.z),
+        ];
+        self.group_name.set(group.name.clone());
+        let inputs = [
+            &mut self.group_x,
+            &mut self.group_y,
+            &mut self.group_z,
+            &mut self.group_scale,
+            &mut self.group_turn,
+            &mut self.group_pivot_x,
+            &mut self.group_pivot_y,
+            &mut self.group_pivot_z,
+        ];
+        for (input, value) in inputs.into_iter().zip(values) {
+            input.set(value);
+        }
+        self.current_screen = CurrentScreen::GroupEditor;
+        self.current_edit = Some(CurrentlyEditing::GroupName);
+        true
+    }
+
+    /// Saves the group editor's settings to the group under the cursor.
+    pub fn save_group(&mut self) -> Result<(), String> {
+        let Some(g) = self.selected_group.filter(|&g| g < self.groups.len()) else {
+            return Err(String::from("No group selected"));
+        };
+        let number =
+            |input: &TextInput, error: &'static str| input.parse::<f64>().map_err(|_| error);
+        let offset = Vec3::new(
+            number(&self.group_x, "Invalid offset")?,
+            number(&self.group_y, "Invalid offset")?,
+            number(&self.group_z, "Invalid offset")?,
+        );
+        let pivot = Point3::new(
+            number(&self.group_pivot_x, "Invalid pivot")?,
+            number(&self.group_pivot_y, "Invalid pivot")?,
+            number(&self.group_pivot_z, "Invalid pivot")?,
+        );
+        let scale = Some(number(&self.group_scale, "Invalid scale")?)
+            .filter(|&scale| scale > 0.0)
+            .ok_or("Invalid scale")?;
+        let turn = number(&self.group_turn, "Invalid turn")?;
+        self.checkpoint();
+        let group = &mut self.groups[g];
+        group.name = self.group_name.trim().to_string();
+        (group.offset, group.pivot, group.scale, group.turn) = (offset, pivot, scale, turn);
+        Ok(())
+    }
+
+    /// Opens the bulk editor on the marked objects, or the selected one, with nothing changed yet.
+    pub fn start_bulk_edit(&mut self) -> bool {
+        if self.targets().is_empty() {
+            return false;
+        }
+        for input in [&mut self.bulk_x, &mut self.bulk_y, &mut self.bulk_z] {
+            input.set("0.0");
+        }
+        self.bulk_scale.set("1.0");
+        self.bulk_material = None;
+        self.current_screen = CurrentScreen::BulkEditor;
+        self.current_edit = Some(CurrentlyEditing::BulkX);
+        true
+    }
+
+    /// Moves, scales and changes the material of the marked objects, or the selected one, with
+    /// the settings from the bulk editor. Planes don't have a radius, so they aren't scaled.
+    pub fn bulk_edit(&mut self) -> Result<(), String> {
+        let coordinate = |input: &TextInput| input.parse::<f64>().map_err(|_| "Invalid offset");
+        let offset = Vec3::new(
+            coordinate(&self.bulk_x)?,
+            coordinate(&self.bulk_y)?,
+            coordinate(&self.bulk_z)?,
+        );
+        let scale: f64 = self
+            .bulk_scale
+            .parse()
+            .ok()
+            .filter(|&scale| scale > 0.0)
+            .ok_or("Invalid scale")?;
+        let targets = self.targets();
+        if targets.is_empty() {
+            return Err(String::from("No object selected"));
+        }
+        self.checkpoint();
+        for i in targets {
+            // the offset is in the scene, so it's turned and scaled back into the object's group
+            let mut object = self.objects[i].translated(self.local_vector(i, offset));
+            if object.kind == ObjectType::Sphere {
+                object.size *= scale;
             }
+            if let Some(material) = self.bulk_material {
+                object.material = material;
+            }
+            self.objects[i] = object;
+        }
+        Ok(())
+    }
+
+    /// The groups `self.objects[index]` is in, starting with the innermost.
+    fn group_chain(&self, index: usize) -> Vec<usize> {
+        let mut chain = Vec::new();
+        let mut group = self.objects[index].group;
+        while let Some(g) = group {
+            chain.push(g);
+            group = self.groups[g].parent;
+        }
+        chain
+    }
+
+    /// A direction in the scene as seen from inside the groups `self.objects[index]` is in.
+    fn local_vector(&self, index: usize, v: Vec3) -> Vec3 {
+        self.group_chain(index)
+            .iter()
+            .rev()
+            .fold(v, |v, &g| self.groups[g].inverse_vector(v))
+    }
+
+    /// Opens the rename screen on the object or group under the cursor, starting from its current
+    /// name.
+    pub fn start_rename(&mut self) -> bool {
+        let name = match self.cursor() {
+            Some(TableRow::Object(i)) => self.objects[i].name.clone(),
+            Some(TableRow::Group(g)) => self.groups[g].name.clone(),
+            None => return false,
+        };
+        self.rename_input.set(name);
+        self.current_screen = CurrentScreen::Rename;
+        self.current_edit = Some(CurrentlyEditing::Rename);
+        true
+    }
+
+    /// Gives the object or group under the cursor the name from the rename screen. An empty name
+    /// goes back to the default.
+    pub fn rename_selected(&mut self) -> bool {
+        let name = self.rename_input.trim().to_string();
+        let current = match self.cursor() {
+            Some(TableRow::Object(i)) => &self.objects[i].name,
+            Some(TableRow::Group(g)) => &self.groups[g].name,
+            None => return false,
+        };
+        if *current == name {
+            return true;
+        }
+        self.checkpoint();
+        match self.cursor() {
+            Some(TableRow::Object(i)) => self.objects[i].name = name,
+            Some(TableRow::Group(g)) => self.groups[g].name = name,
+            None => {}
+        }
+        true
+    }
+
+    /// Hides the marked objects, or the selected one, from the preview and render. If they're all
+    /// hidden already they're shown again instead.
+    pub fn toggle_hidden(&mut self) -> bool {
+        let targets = self.targets();
+        if targets.is_empty() {
+            return false;
+        }
+        self.checkpoint();
+        let hidden = !targets.iter().all(|&i| self.objects[i].hidden);
+        for i in targets {
+            self.objects[i].hidden = hidden;
         }
+        true
+    }
+
+    /// Solos the selected object, so it's the only one in the preview and render, or turns solo
+    /// off again. Only one object can be soloed at a time.
+    pub fn toggle_solo(&mut self) -> bool {
+        let Some(selected) = self.selected_object.filter(|&i| i < self.objects.len()) else {
+            return false;
+        };
+        self.checkpoint();
+        let solo = !self.objects[selected].solo;
+        for (i, object) in self.objects.iter_mut().enumerate() {
+            object.solo = solo && i == selected;
+        }
+        true
+    }
+
+    fn array_settings(&self) -> Result<ArraySettings, String> {
+        let count = |input: &TextInput| {
+            input
+                .parse::<usize>()
+                .ok()
+                .filter(|&count| count > 0)
+                .ok_or("Invalid count")
+        };
+        let coordinate = |input: &TextInput| input.parse::<f64>().map_err(|_| "Invalid offset");
+        Ok(ArraySettings {
+            pattern: self.array_pattern,
+            count: count(&self.array_count)?,
+            rows: count(&self.array_rows)?,
+            vector: Vec3::new(
+                coordinate(&self.array_x)?,
+                coordinate(&self.array_y)?,
+                coordinate(&self.array_z)?,
+            ),
+            cycle_materials: self.array_cycle_materials,
+        })
+    }
+
+    /// Adds copies of `self.objects[index]` right after it, as one change that can be undone. The
+    /// settings are in the scene's directions, even when the object is in a turned or scaled group.
+    fn insert_copies(&mut self, index: usize, settings: &ArraySettings) -> usize {
+        let chain = self.group_chain(index);
+        let mut copies = array::copies(&self.placed(index), settings, self.materials.len());
+        for copy in &mut copies {
+            // back into the groups the object is in
+            *copy = chain.iter().rev().fold(copy.clone(), |copy, &g| {
+                self.groups[g].inverse_transform(&copy)
+            });
+            // only one object is soloed at a time, and that stays the original
+            copy.solo = false;
+        }
+        let added = copies.len();
+        self.checkpoint();
+        // the rows after the copies move down, so the marks would land on the wrong objects
+        self.marked.clear();
+        let after = index + 1;
+        self.objects.splice(after..after, copies);
+        added
+    }
+
+    /// Copies the selected object with the settings from the array editor.
+    pub fn array_selected(&mut self) -> Result<(), String> {
+        let selected = self
+            .selected_object
+            .filter(|&i| i < self.objects.len())
+            .ok_or("No object selected")?;
+        let settings = self.array_settings()?;
+        self.insert_copies(selected, &settings);
+        Ok(())
+    }
+
+    /// Copies the selected object once, moved by the array editor's X, Y and Z, and selects the
+    /// copy so pressing it again makes a row.
+    pub fn duplicate_selected(&mut self) -> bool {
+        let Some(selected) = self.selected_object.filter(|&i| i < self.objects.len()) else {
+            return false;
+        };
+        let Ok(settings) = self.array_settings() else {
+            return false;
+        };
+        let settings = ArraySettings {
+            pattern: ArrayPattern::Line,
+            count: 1,
+            ..settings
+        };
+        self.insert_copies(selected, &settings);
+        self.set_cursor(Some(TableRow::Object(selected + 1)));
+        true
+    }
+
+    /// Opens the color picker on the focused field. Returns false if it isn't a color field.
+    pub fn open_picker(&mut self) -> bool {
+        let Some(id) = self.current_edit else {
+            return false;
+        };
+        let Some(FieldKind::Color { input, .. }) = form::field(id).map(|field| &field.kind) else {
+            return false;
+        };
+        // a color that doesn't parse yet starts from white
+        let rgb = hex_bytes(input(self)).unwrap_or([255, 255, 255]);
+        self.picker = Some(ColorPicker::new(id, rgb));
+        true
+    }
+
+    /// Closes the color picker, writing its color into the field it was opened on.
+    pub fn apply_picker(&mut self) {
+        let Some(picker) = self.picker.take() else {
+            return;
+        };
+        if let Some(input) = form::field(picker.field).and_then(|field| field.input_mut(self)) {
+            input.set(picker.hex());
+        }
+        self.recent_colors.retain(|&rgb| rgb != picker.rgb());
+        self.recent_colors.insert(0, picker.rgb());
+        self.recent_colors.truncate(RECENT_COLORS);
+    }
+
+    /// Whether `field` has focus, used to show the caret.
+    pub fn is_editing(&self, field: CurrentlyEditing) -> bool {
+        self.current_edit == Some(field)
+    }
+
+    /// Moves focus to the next or previous field on the current screen's form, skipping the ones
+    /// that are hidden.
+    pub fn change_editing(&mut self, forwards: bool) {
+        let fields: Vec<&Field> = form::form(&self.current_screen)
+            .iter()
+            .flat_map(|row| row.iter())
+            .filter(|field| (field.visible)(self))
+            .collect();
+        if fields.is_empty() {
+            return;
+        }
+        let count = fields.len();
+        let next = match self
+            .current_edit
+            .and_then(|id| fields.iter().position(|field| field.id == id))
+        {
+            Some(i) if forwards => (i + 1) % count,
+            Some(i) => (i + count - 1) % count,
+            None => 0,
+        };
+        self.current_edit = Some(fields[next].id);
     }
 }
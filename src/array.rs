@@ -0,0 +1,87 @@
+#![warn(clippy::pedantic)]
+//! Copying an object many times at once, along a line, in a grid or around a circle.
+
+use std::f64::consts::TAU;
+
+use rtwlib::vec3::Vec3;
+
+use crate::app::SceneObject;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum ArrayPattern {
+    /// Each copy is moved by the offset from the one before
+    Line,
+    /// Columns along X and rows along Z, with the object in the first cell
+    Grid,
+    /// Spread evenly around a vertical axis, on the circle the object is already on
+    Circle,
+}
+
+impl std::fmt::Display for ArrayPattern {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ArrayPattern::Line => write!(f, "Line"),
+            ArrayPattern::Grid => write!(f, "Grid"),
+            ArrayPattern::Circle => write!(f, "Circle"),
+        }
+    }
+}
+
+impl ArrayPattern {
+    pub fn cycle(self, forwards: bool) -> Self {
+        match (self, forwards) {
+            (ArrayPattern::Line, true) | (ArrayPattern::Circle, false) => ArrayPattern::Grid,
+            (ArrayPattern::Grid, true) | (ArrayPattern::Line, false) => ArrayPattern::Circle,
+            (ArrayPattern::Circle, true) | (ArrayPattern::Grid, false) => ArrayPattern::Line,
+        }
+    }
+}
+
+pub struct ArraySettings {
+    pub pattern: ArrayPattern,
+    /// Copies for a line or circle, columns for a grid
+    pub count: usize,
+    pub rows: usize,
+    /// The offset for a line, spacing for a grid (Y is unused), or the center of a circle
+    pub vector: Vec3,
+    /// Give each copy the next material along from the one before
+    pub cycle_materials: bool,
+}
+
+/// The copies of `object` laid out by `settings`, not including the object itself.
+#[allow(clippy::cast_precision_loss)]
+pub fn copies(
+    object: &SceneObject,
+    settings: &ArraySettings,
+    materials: usize,
+) -> Vec<SceneObject> {
+    let vector = settings.vector;
+    let mut copies: Vec<SceneObject> = match settings.pattern {
+        ArrayPattern::Line => (1..=settings.count)
+            .map(|i| object.translated(vector * i as f64))
+            .collect(),
+        ArrayPattern::Grid => (0..settings.rows)
+            .flat_map(|row| (0..settings.count).map(move |column| (column, row)))
+            .skip(1)
+            .map(|(column, row)| {
+                object.translated(Vec3::new(
+                    vector.x * column as f64,
+                    0.0,
+                    vector.z * row as f64,
+                ))
+            })
+            .collect(),
+        ArrayPattern::Circle => {
+            let step = TAU / (settings.count + 1) as f64;
+            (1..=settings.count)
+                .map(|i| object.rotated_y(vector, step * i as f64))
+                .collect()
+        }
+    };
+    if settings.cycle_materials && materials > 0 {
+        for (i, copy) in copies.iter_mut().enumerate() {
+            copy.material = (object.material + i + 1) % materials;
+        }
+    }
+    copies
+}
@@ -0,0 +1,15 @@
+#![warn(clippy::pedantic)]
+//! Where the user's settings files live.
+
+use std::{env, path::PathBuf};
+
+/// The app's folder in the user's config dir: `$XDG_CONFIG_HOME/rtw-tui`, falling back to
+/// `~/.config/rtw-tui`, or `%APPDATA%\rtw-tui` on Windows.
+pub fn config_dir() -> Option<PathBuf> {
+    let var = |name: &str| env::var_os(name).filter(|value| !value.is_empty());
+    let base = var("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| var("HOME").map(|home| PathBuf::from(home).join(".config")))
+        .or_else(|| var("APPDATA").map(PathBuf::from))?;
+    Some(base.join("rtw-tui"))
+}
@@ -0,0 +1,66 @@
+#![warn(clippy::pedantic)]
+//! Color reduction for terminals without 24-bit color. An ordered (Bayer) dither is used so the
+//! preview doesn't shimmer between redraws the way error diffusion would.
+
+use ratatui::style::Color;
+
+const BAYER_4X4: [[u8; 4]; 4] = [[0, 8, 2, 10], [12, 4, 14, 6], [3, 11, 1, 9], [15, 7, 13, 5]];
+
+/// The 16 ANSI colors, with their usual xterm values
+const ANSI_16: [(Color, [u8; 3]); 16] = [
+    (Color::Black, [0, 0, 0]),
+    (Color::Red, [205, 0, 0]),
+    (Color::Green, [0, 205, 0]),
+    (Color::Yellow, [205, 205, 0]),
+    (Color::Blue, [0, 0, 238]),
+    (Color::Magenta, [205, 0, 205]),
+    (Color::Cyan, [0, 205, 205]),
+    (Color::Gray, [229, 229, 229]),
+    (Color::DarkGray, [127, 127, 127]),
+    (Color::LightRed, [255, 0, 0]),
+    (Color::LightGreen, [0, 255, 0]),
+    (Color::LightYellow, [255, 255, 0]),
+    (Color::LightBlue, [92, 92, 255]),
+    (Color::LightMagenta, [255, 0, 255]),
+    (Color::LightCyan, [0, 255, 255]),
+    (Color::White, [255, 255, 255]),
+];
+
+/// Dither threshold for pixel (x, y), between -0.5 and 0.5.
+pub fn threshold(x: usize, y: usize) -> f64 {
+    (f64::from(BAYER_4X4[y % 4][x % 4]) + 0.5) / 16.0 - 0.5
+}
+
+/// Perceived brightness of a pixel, from 0.0 to 1.0.
+pub fn luminance(pixel: [u8; 3]) -> f64 {
+    (0.2126 * f64::from(pixel[0]) + 0.7152 * f64::from(pixel[1]) + 0.0722 * f64::from(pixel[2]))
+        / 255.0
+}
+
+/// Moves each channel of a pixel by the dither threshold, scaled to `spread`.
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn dithered(pixel: [u8; 3], x: usize, y: usize, spread: f64) -> [u8; 3] {
+    let offset = threshold(x, y) * spread;
+    pixel.map(|c| (f64::from(c) + offset).clamp(0.0, 255.0) as u8)
+}
+
+/// Nearest color in the 256 color palette's 6x6x6 cube.
+pub fn to_256(pixel: [u8; 3], x: usize, y: usize) -> Color {
+    let levels = dithered(pixel, x, y, 51.0).map(|c| (u16::from(c) * 5 + 127) / 255);
+    let index = levels[0] * 36 + levels[1] * 6 + levels[2];
+    Color::Indexed(16 + u8::try_from(index).unwrap_or(0))
+}
+
+/// Nearest of the 16 ANSI colors.
+pub fn to_16(pixel: [u8; 3], x: usize, y: usize) -> Color {
+    let pixel = dithered(pixel, x, y, 96.0);
+    let distance = |color: &[u8; 3]| -> i32 {
+        (0..3)
+            .map(|c| (i32::from(pixel[c]) - i32::from(color[c])).pow(2))
+            .sum()
+    };
+    ANSI_16
+        .iter()
+        .min_by_key(|(_, rgb)| distance(rgb))
+        .map_or(Color::Reset, |(color, _)| *color)
+}
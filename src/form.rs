@@ -0,0 +1,715 @@
+#![warn(clippy::pedantic)]
+//! The fields on each editor screen, declared once. The declarations drive the focus order, which
+//! input keys go to, Up/Down on choices, and how the fields are drawn, so adding a field to a
+//! screen only takes a new entry here.
+
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style, Stylize},
+    text::Line,
+    widgets::{Block, BorderType, Borders, Paragraph},
+    Frame,
+};
+
+use crate::{
+    app::{CurrentScreen, CurrentlyEditing, MaterialType, SkyType, RESOLUTION_PRESETS},
+    array::ArrayPattern,
+    input::TextInput,
+    projection::Projection,
+    App,
+};
+
+/// A form is laid out as rows of fields, focus goes through them left to right, top to bottom.
+pub type Form = &'static [&'static [Field]];
+
+pub enum Label {
+    Fixed(&'static str),
+    /// A title that depends on the other inputs, like "Radius" or "Y" for an object's size
+    Dynamic(fn(&App) -> String),
+}
+
+pub enum FieldKind {
+    Text {
+        input: fn(&App) -> &TextInput,
+        input_mut: fn(&mut App) -> &mut TextInput,
+        /// Whether the text can be used, fields that fail are drawn with a red title
+        validate: fn(&str) -> bool,
+    },
+    /// A hex color, drawn in the color it describes
+    Color {
+        input: fn(&App) -> &TextInput,
+        input_mut: fn(&mut App) -> &mut TextInput,
+    },
+    /// One of a set of values, stepped through with Up/Down
+    Choice {
+        value: fn(&App) -> String,
+        cycle: fn(&mut App, bool),
+    },
+}
+
+pub struct Field {
+    pub id: CurrentlyEditing,
+    pub label: Label,
+    pub kind: FieldKind,
+    /// Whether the field is shown and can take focus. Hidden fields keep their space in the row
+    pub visible: fn(&App) -> bool,
+    /// Narrowest the field can be drawn, in cells
+    pub width: u16,
+}
+
+/// How a form's fields are drawn.
+#[derive(Clone, Copy)]
+pub enum FormStyle {
+    /// Gray boxes without borders, used by the popup editors
+    Flat,
+    /// Bordered boxes with a double border on the focused field, used by the render settings
+    Boxed,
+}
+
+fn always(_: &App) -> bool {
+    true
+}
+
+fn is_number(text: &str) -> bool {
+    text.parse::<f64>().is_ok()
+}
+
+/// For counts and pixel sizes, which can't have a fraction or be negative.
+fn is_whole_number(text: &str) -> bool {
+    text.parse::<u32>().is_ok()
+}
+
+fn is_filled(text: &str) -> bool {
+    !text.trim().is_empty()
+}
+
+/// Bytes of a six digit hex color.
+pub fn hex_bytes(text: &str) -> Option<[u8; 3]> {
+    if text.len() != 6 || !text.is_ascii() {
+        return None;
+    }
+    let byte = |i: usize| u8::from_str_radix(&text[i..i + 2], 16).ok();
+    Some([byte(0)?, byte(2)?, byte(4)?])
+}
+
+/// Steps `index` through `len` values, wrapping around at both ends.
+fn cycle(index: usize, len: usize, forwards: bool) -> usize {
+    let len = len.max(1);
+    if forwards {
+        (index + 1) % len
+    } else {
+        (index + len - 1) % len
+    }
+}
+
+macro_rules! text_field {
+    ($id:ident, $label:expr, $input:ident, $width:expr, $validate:expr) => {
+        Field {
+            id: CurrentlyEditing::$id,
+            label: $label,
+            kind: FieldKind::Text {
+                input: |app| &app.$input,
+                input_mut: |app| &mut app.$input,
+                validate: $validate,
+            },
+            visible: always,
+            width: $width,
+        }
+    };
+}
+
+macro_rules! color_field {
+    ($id:ident, $label:expr, $input:ident, $width:expr) => {
+        Field {
+            id: CurrentlyEditing::$id,
+            label: $label,
+            kind: FieldKind::Color {
+                input: |app| &app.$input,
+                input_mut: |app| &mut app.$input,
+            },
+            visible: always,
+            width: $width,
+        }
+    };
+}
+
+/// Labels for the object editor, spheres have a center and radius while planes have a normal and
+/// a height.
+fn object_label(app: &App, sphere: &str, plane: &str) -> String {
+    match app.type_input {
+        0 => sphere.to_string(),
+        _ => plane.to_string(),
+    }
+}
+
+pub const OBJECT_FORM: Form = &[&[
+    Field {
+        id: CurrentlyEditing::Type,
+        label: Label::Fixed("Type"),
+        kind: FieldKind::Choice {
+            value: App::get_type,
+            cycle: |app, forwards| app.type_input = cycle(app.type_input, 2, forwards),
+        },
+        visible: always,
+        width: 6,
+    },
+    text_field!(
+        Size,
+        Label::Dynamic(|app| object_label(app, "Radius", "Y")),
+        size_input,
+        6,
+        is_number
+    ),
+    text_field!(
+        PositionX,
+        Label::Dynamic(|app| object_label(app, "X", "Facing X")),
+        position_input_x,
+        4,
+        is_number
+    ),
+    text_field!(
+        PositionY,
+        Label::Dynamic(|app| object_label(app, "Y", "Facing Y")),
+        position_input_y,
+        4,
+        is_number
+    ),
+    text_field!(
+        PositionZ,
+        Label::Dynamic(|app| object_label(app, "Z", "Facing Z")),
+        position_input_z,
+        4,
+        is_number
+    ),
+    Field {
+        id: CurrentlyEditing::Material,
+        label: Label::Fixed("Material"),
+        kind: FieldKind::Choice {
+            value: |app| {
+                app.materials
+                    .get(app.material_input)
+                    .map_or_else(String::new, |material| material.name.clone())
+            },
+            cycle: |app, forwards| {
+                app.material_input = cycle(app.material_input, app.materials.len(), forwards);
+            },
+        },
+        visible: always,
+        width: 16,
+    },
+    // left empty, the object is called by its type and number
+    text_field!(
+        ObjectName,
+        Label::Fixed("Name"),
+        object_name_input,
+        10,
+        |_| true
+    ),
+]];
+
+pub const RENAME_FORM: Form = &[&[text_field!(
+    Rename,
+    Label::Fixed("Name (empty for the default)"),
+    rename_input,
+    20,
+    |_| true
+)]];
+
+pub const MATERIAL_FORM: Form = &[&[
+    Field {
+        id: CurrentlyEditing::MatType,
+        label: Label::Fixed("Type"),
+        kind: FieldKind::Choice {
+            value: |app| {
+                app.mat_type_input
+                    .map_or_else(String::new, |kind| kind.to_string())
+            },
+            cycle: |app, forwards| {
+                const ORDER: [MaterialType; 4] = [
+                    MaterialType::Lambertian,
+                    MaterialType::Metal,
+                    MaterialType::Dielectric,
+                    MaterialType::Normal,
+                ];
+                if let Some(kind) = app.mat_type_input {
+                    let index = ORDER.iter().position(|&k| k == kind).unwrap_or(0);
+                    app.mat_type_input = Some(ORDER[cycle(index, ORDER.len(), forwards)]);
+                }
+            },
+        },
+        visible: always,
+        width: 7,
+    },
+    color_field!(MatColor, Label::Fixed("Color"), mat_color_input, 7),
+    Field {
+        visible: |app| {
+            matches!(
+                app.mat_type_input,
+                Some(MaterialType::Metal | MaterialType::Dielectric)
+            )
+        },
+        ..text_field!(
+            MatProperty,
+            Label::Dynamic(|app| match app.mat_type_input {
+                Some(MaterialType::Dielectric) => String::from("IOR"),
+                _ => String::from("Roughness"),
+            }),
+            mat_other_input,
+            5,
+            is_number
+        )
+    },
+    text_field!(MatName, Label::Fixed("Name"), mat_name_input, 15, is_filled),
+]];
+
+pub const SKY_FORM: Form = &[&[
+    Field {
+        id: CurrentlyEditing::SkyType,
+        label: Label::Fixed("Type"),
+        kind: FieldKind::Choice {
+            value: |app| match app.sky_type {
+                SkyType::Gradient => String::from("Gradient"),
+                SkyType::Solid => String::from("Solid"),
+            },
+            cycle: |app, _| {
+                app.sky_type = match app.sky_type {
+                    SkyType::Gradient => SkyType::Solid,
+                    SkyType::Solid => SkyType::Gradient,
+                }
+            },
+        },
+        visible: always,
+        width: 8,
+    },
+    color_field!(
+        SkyColor1,
+        Label::Dynamic(|app| match app.sky_type {
+            SkyType::Gradient => String::from("Top Color"),
+            SkyType::Solid => String::from("Color"),
+        }),
+        sky_color1,
+        7
+    ),
+    Field {
+        visible: |app| app.sky_type == SkyType::Gradient,
+        ..color_field!(SkyColor2, Label::Fixed("Bottom Color"), sky_color2, 7)
+    },
+]];
+
+/// Labels for the array editor's vector, which is an offset, a spacing or a center.
+fn array_label(app: &App, axis: &str) -> String {
+    match app.array_pattern {
+        ArrayPattern::Line => format!("Offset {axis}"),
+        ArrayPattern::Grid => format!("Spacing {axis}"),
+        ArrayPattern::Circle => format!("Center {axis}"),
+    }
+}
+
+pub const ARRAY_FORM: Form = &[
+    &[
+        Field {
+            id: CurrentlyEditing::ArrayPattern,
+            label: Label::Fixed("Pattern"),
+            kind: FieldKind::Choice {
+                value: |app| app.array_pattern.to_string(),
+                cycle: |app, forwards| app.array_pattern = app.array_pattern.cycle(forwards),
+            },
+            visible: always,
+            width: 8,
+        },
+        text_field!(
+            ArrayCount,
+            Label::Dynamic(|app| match app.array_pattern {
+                ArrayPattern::Grid => String::from("Columns"),
+                ArrayPattern::Line | ArrayPattern::Circle => String::from("Copies"),
+            }),
+            array_count,
+            8,
+            is_whole_number
+        ),
+        Field {
+            visible: |app| app.array_pattern == ArrayPattern::Grid,
+            ..text_field!(
+                ArrayRows,
+                Label::Fixed("Rows"),
+                array_rows,
+                5,
+                is_whole_number
+            )
+        },
+        Field {
+            id: CurrentlyEditing::ArrayMaterials,
+            label: Label::Fixed("Materials"),
+            kind: FieldKind::Choice {
+                value: |app| {
+                    String::from(if app.array_cycle_materials {
+                        "Cycle"
+                    } else {
+                        "Same"
+                    })
+                },
+                cycle: |app, _| app.array_cycle_materials = !app.array_cycle_materials,
+            },
+            visible: always,
+            width: 10,
+        },
+    ],
+    &[
+        text_field!(
+            ArrayX,
+            Label::Dynamic(|app| array_label(app, "X")),
+            array_x,
+            10,
+            is_number
+        ),
+        // a grid lies flat and a circle turns around a vertical axis, so neither uses Y
+        Field {
+            visible: |app| app.array_pattern == ArrayPattern::Line,
+            ..text_field!(
+                ArrayY,
+                Label::Dynamic(|app| array_label(app, "Y")),
+                array_y,
+                10,
+                is_number
+            )
+        },
+        text_field!(
+            ArrayZ,
+            Label::Dynamic(|app| array_label(app, "Z")),
+            array_z,
+            10,
+            is_number
+        ),
+    ],
+];
+
+pub const BULK_FORM: Form = &[&[
+    text_field!(BulkX, Label::Fixed("Move X"), bulk_x, 6, is_number),
+    text_field!(BulkY, Label::Fixed("Move Y"), bulk_y, 6, is_number),
+    text_field!(BulkZ, Label::Fixed("Move Z"), bulk_z, 6, is_number),
+    text_field!(BulkScale, Label::Fixed("Scale"), bulk_scale, 6, is_number),
+    Field {
+        id: CurrentlyEditing::BulkMaterial,
+        label: Label::Fixed("Material"),
+        kind: FieldKind::Choice {
+            value: |app| match app.bulk_material.and_then(|i| app.materials.get(i)) {
+                Some(material) => material.name.clone(),
+                None => String::from("Keep"),
+            },
+            // "Keep" comes before the first material
+            cycle: |app, forwards| {
+                let index = app.bulk_material.map_or(0, |i| i + 1);
+                app.bulk_material = cycle(index, app.materials.len() + 1, forwards).checked_sub(1);
+            },
+        },
+        visible: always,
+        width: 16,
+    },
+]];
+
+pub const GROUP_FORM: Form = &[
+    &[
+        text_field!(GroupName, Label::Fixed("Name"), group_name, 10, |_| true),
+        text_field!(GroupX, Label::Fixed("Move X"), group_x, 6, is_number),
+        text_field!(GroupY, Label::Fixed("Move Y"), group_y, 6, is_number),
+        text_field!(GroupZ, Label::Fixed("Move Z"), group_z, 6, is_number),
+        text_field!(GroupScale, Label::Fixed("Scale"), group_scale, 6, is_number),
+        text_field!(GroupTurn, Label::Fixed("Turn °"), group_turn, 6, is_number),
+    ],
+    &[
+        text_field!(
+            GroupPivotX,
+            Label::Fixed("Pivot X"),
+            group_pivot_x,
+            6,
+            is_number
+        ),
+        text_field!(
+            GroupPivotY,
+            Label::Fixed("Pivot Y"),
+            group_pivot_y,
+            6,
+            is_number
+        ),
+        text_field!(
+            GroupPivotZ,
+            Label::Fixed("Pivot Z"),
+            group_pivot_z,
+            6,
+            is_number
+        ),
+    ],
+];
+
+pub const RENDER_FORM: Form = &[
+    &[
+        Field {
+            id: CurrentlyEditing::Preset,
+            label: Label::Fixed("Preset"),
+            kind: FieldKind::Choice {
+                value: |app| match app.resolution_preset() {
+                    Some(i) => RESOLUTION_PRESETS[i].0.to_string(),
+                    None => String::from("Custom"),
+                },
+                cycle: App::cycle_resolution_preset,
+            },
+            visible: always,
+            width: 9,
+        },
+        text_field!(
+            Width,
+            Label::Fixed("Image Width"),
+            image_width,
+            5,
+            is_whole_number
+        ),
+        text_field!(
+            Height,
+            Label::Fixed("Image Height"),
+            image_height,
+            5,
+            is_whole_number
+        ),
+        Field {
+            id: CurrentlyEditing::AspectLock,
+            label: Label::Fixed("Aspect"),
+            kind: FieldKind::Choice {
+                value: |app| match app.aspect_lock {
+                    Some(aspect) => format!("{aspect:.2}:1"),
+                    None => String::from("Free"),
+                },
+                cycle: |app, _| app.toggle_aspect_lock(),
+            },
+            visible: always,
+            width: 6,
+        },
+        text_field!(
+            Scale,
+            Label::Fixed("Scale %"),
+            render_scale,
+            5,
+            is_whole_number
+        ),
+        text_field!(
+            ImgName,
+            Label::Fixed("Filename"),
+            image_name_input,
+            10,
+            is_filled
+        ),
+    ],
+    &[
+        text_field!(
+            Samples,
+            Label::Fixed("Samples"),
+            samples,
+            6,
+            is_whole_number
+        ),
+        text_field!(
+            Bounces,
+            Label::Fixed("Bounces"),
+            bounces,
+            6,
+            is_whole_number
+        ),
+    ],
+    &[
+        text_field!(CamX, Label::Fixed("Camera X"), camx, 6, is_number),
+        text_field!(CamY, Label::Fixed("Camera Y"), camy, 6, is_number),
+        text_field!(CamZ, Label::Fixed("Camera Z"), camz, 6, is_number),
+        text_field!(LookX, Label::Fixed("Look X"), lookx, 6, is_number),
+        text_field!(LookY, Label::Fixed("Look Y"), looky, 6, is_number),
+        text_field!(LookZ, Label::Fixed("Look Z"), lookz, 6, is_number),
+    ],
+    &[
+        text_field!(Fov, Label::Fixed("FOV"), fov, 6, is_number),
+        text_field!(
+            FocusDist,
+            Label::Dynamic(|app| match app.auto_focus_distance() {
+                Some(distance) => format!("Focus Distance (auto {distance:.2})"),
+                None => String::from("Focus Distance"),
+            }),
+            focus_dist,
+            6,
+            is_number
+        ),
+        text_field!(
+            Aperture,
+            Label::Fixed("Blur amount"),
+            aperture,
+            6,
+            is_number
+        ),
+        Field {
+            id: CurrentlyEditing::Projection,
+            label: Label::Fixed("Projection"),
+            kind: FieldKind::Choice {
+                value: |app| app.projection.to_string(),
+                cycle: |app, _| {
+                    app.projection = match app.projection {
+                        Projection::Perspective => Projection::Orthographic,
+                        Projection::Orthographic => Projection::Perspective,
+                    }
+                },
+            },
+            visible: always,
+            width: 6,
+        },
+        Field {
+            visible: |app| app.projection == Projection::Orthographic,
+            ..text_field!(
+                OrthoWidth,
+                Label::Fixed("Ortho Width"),
+                ortho_width,
+                6,
+                is_number
+            )
+        },
+    ],
+];
+
+/// The form shown on a screen, empty for screens without one.
+pub fn form(screen: &CurrentScreen) -> Form {
+    match screen {
+        CurrentScreen::Editor => OBJECT_FORM,
+        CurrentScreen::MaterialEditor => MATERIAL_FORM,
+        CurrentScreen::SkyEditor => SKY_FORM,
+        CurrentScreen::Render => RENDER_FORM,
+        CurrentScreen::ArrayEditor => ARRAY_FORM,
+        CurrentScreen::Rename => RENAME_FORM,
+        CurrentScreen::BulkEditor => BULK_FORM,
+        CurrentScreen::GroupEditor => GROUP_FORM,
+        _ => &[],
+    }
+}
+
+/// Looks up a field by id, in any form.
+pub fn field(id: CurrentlyEditing) -> Option<&'static Field> {
+    [
+        OBJECT_FORM,
+        MATERIAL_FORM,
+        SKY_FORM,
+        RENDER_FORM,
+        ARRAY_FORM,
+        RENAME_FORM,
+        BULK_FORM,
+        GROUP_FORM,
+    ]
+    .into_iter()
+    .flatten()
+    .flat_map(|row| row.iter())
+    .find(|field| field.id == id)
+}
+
+impl Field {
+    pub fn label(&self, app: &App) -> String {
+        match self.label {
+            Label::Fixed(label) => label.to_string(),
+            Label::Dynamic(label) => label(app),
+        }
+    }
+
+    /// The text input behind the field, if it's typed into.
+    pub fn input_mut<'a>(&self, app: &'a mut App) -> Option<&'a mut TextInput> {
+        match self.kind {
+            FieldKind::Text { input_mut, .. } | FieldKind::Color { input_mut, .. } => {
+                Some(input_mut(app))
+            }
+            FieldKind::Choice { .. } => None,
+        }
+    }
+
+    /// Whether the field holds a number that Up/Down step.
+    pub fn is_stepped(&self, app: &App) -> bool {
+        match self.kind {
+            FieldKind::Text { input, .. } => input(app).is_numeric(),
+            FieldKind::Color { .. } | FieldKind::Choice { .. } => false,
+        }
+    }
+
+    pub fn is_valid(&self, app: &App) -> bool {
+        match self.kind {
+            FieldKind::Text {
+                input, validate, ..
+            } => validate(input(app)),
+            FieldKind::Color { input, .. } => hex_bytes(input(app)).is_some(),
+            FieldKind::Choice { .. } => true,
+        }
+    }
+
+    fn content<'a>(&self, app: &'a App) -> Line<'a> {
+        let focused = app.is_editing(self.id);
+        let selected = Style::default()
+            .bg(app.theme.selection)
+            .fg(app.theme.selection_text);
+        match self.kind {
+            FieldKind::Text { input, .. } | FieldKind::Color { input, .. } => {
+                input(app).line(focused, selected)
+            }
+            FieldKind::Choice { value, .. } => Line::raw(value(app)),
+        }
+    }
+
+    fn block(&self, app: &App, style: FormStyle) -> Block<'static> {
+        let focused = app.is_editing(self.id);
+        let theme = &app.theme;
+        let mut block = match style {
+            FormStyle::Flat => Block::default()
+                .borders(Borders::NONE)
+                .bg(theme.field)
+                .fg(theme.text),
+            FormStyle::Boxed => Block::default()
+                .borders(Borders::ALL)
+                .border_type(BorderType::Plain),
+        }
+        .title(self.label(app));
+
+        if let FieldKind::Color { input, .. } = self.kind {
+            // colors that don't parse show up magenta by default, like missing textures
+            let rgb = hex_bytes(input(app));
+            let color = rgb.map_or(theme.swatch_invalid, |[r, g, b]| Color::Rgb(r, g, b));
+            block = if focused {
+                let inverse = rgb.map_or(theme.field_focused_text, |[r, g, b]| {
+                    Color::Rgb(255 - r, 255 - g, 255 - b)
+                });
+                block.style(Style::default().bg(color).fg(inverse))
+            } else {
+                block.bg(theme.swatch).fg(color)
+            };
+        } else if focused {
+            block = match style {
+                FormStyle::Flat => block.style(
+                    Style::default()
+                        .bg(theme.field_focused)
+                        .fg(theme.field_focused_text),
+                ),
+                FormStyle::Boxed => block
+                    .border_type(BorderType::Double)
+                    .style(Style::default().add_modifier(Modifier::BOLD)),
+            };
+        }
+        if !self.is_valid(app) {
+            block = block.title_style(Style::default().fg(theme.error));
+        }
+        block
+    }
+}
+
+/// Draws a row of fields side by side across `area`.
+pub fn render_row(frame: &mut Frame, area: Rect, app: &App, row: &[Field], style: FormStyle) {
+    let chunks = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints(row.iter().map(|field| Constraint::Min(field.width)))
+        .spacing(match style {
+            FormStyle::Flat => 2,
+            FormStyle::Boxed => 1,
+        })
+        .split(area);
+    for (field, &chunk) in row.iter().zip(chunks.iter()) {
+        if (field.visible)(app) {
+            let widget = Paragraph::new(field.content(app)).block(field.block(app, style));
+            frame.render_widget(widget, chunk);
+        }
+    }
+}
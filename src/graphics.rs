@@ -0,0 +1,377 @@
+#![warn(clippy::pedantic)]
+//! Encoders for terminal image protocols, used to show the preview at full pixel resolution.
+//! The escape sequences can't go through ratatui's buffer, so the preview leaves its area blank and
+//! queues the image here, to be written straight to the terminal after the frame is drawn.
+
+use std::{
+    fmt::Write as _,
+    io::{stdout, Write},
+    time::Duration,
+};
+
+use color_eyre::Result;
+use crossterm::{
+    cursor::{MoveTo, RestorePosition, SavePosition},
+    queue,
+    style::Print,
+    terminal::window_size,
+};
+use ratatui::layout::Rect;
+
+use crate::preview::PreviewMode;
+
+/// Kitty image id used for the preview, sending a new image with the same id replaces the old one
+const KITTY_IMAGE_ID: u32 = 1;
+/// Cell size to assume when the terminal won't report its size in pixels
+const FALLBACK_CELL_SIZE: (u16, u16) = (8, 16);
+/// How long to wait for the terminal to say what it supports
+const QUERY_TIMEOUT: Duration = Duration::from_millis(200);
+
+/// An encoded image waiting to be written over `area`.
+pub struct PendingImage {
+    pub area: Rect,
+    pub mode: PreviewMode,
+    pub escape: String,
+}
+
+/// Images queued by the last draw, and what's still on screen from the one before.
+#[derive(Default)]
+pub struct GraphicsOutput {
+    pub pending: Option<PendingImage>,
+    pub shown: Option<PreviewMode>,
+}
+
+impl GraphicsOutput {
+    /// Writes the queued image to the terminal, or removes the last one if nothing was queued.
+    /// Returns true when the screen has to be cleared and redrawn to get rid of an old image.
+    pub fn flush(&mut self) -> Result<bool> {
+        let mut out = stdout();
+        match self.pending.take() {
+            Some(image) => {
+                queue!(
+                    out,
+                    SavePosition,
+                    MoveTo(image.area.x, image.area.y),
+                    Print(&image.escape),
+                    RestorePosition
+                )?;
+                self.shown = Some(image.mode);
+            }
+            None => match self.shown.take() {
+                Some(PreviewMode::Kitty) => {
+                    queue!(
+                        out,
+                        Print(format!("\x1b_Ga=d,d=I,i={KITTY_IMAGE_ID},q=2\x1b\\"))
+                    )?;
+                }
+                // sixel and iTerm2 images are painted into the cells, so only a full redraw clears them
+                Some(_) => return Ok(true),
+                None => {}
+            },
+        }
+        out.flush()?;
+        Ok(false)
+    }
+}
+
+/// Image protocols the terminal says it supports.
+pub struct Support {
+    pub kitty: bool,
+    pub sixel: bool,
+}
+
+/// Asks the terminal which image protocols it supports, with a Kitty graphics query followed by a
+/// primary device attributes request. Every terminal answers the attributes, after any answer to
+/// the Kitty query, and lists `4` in them if it does Sixel. Needs raw mode, or the answers are
+/// echoed and held until Enter. Returns `None` if there's no answer in time.
+#[cfg(unix)]
+pub fn query() -> Option<Support> {
+    use std::{fs::OpenOptions, io::Read, os::fd::AsRawFd, time::Instant};
+
+    let mut tty = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open("/dev/tty")
+        .ok()?;
+    tty.write_all(b"\x1b_Gi=31,s=1,v=1,a=q,t=d,f=24;AAAA\x1b\\\x1b[c")
+        .ok()?;
+    tty.flush().ok()?;
+    let deadline = Instant::now() + QUERY_TIMEOUT;
+    let mut reply = Vec::new();
+    // a byte at a time, stopping right after the attributes so no key presses get taken
+    let reply = loop {
+        let left = deadline.saturating_duration_since(Instant::now());
+        if left.is_zero() {
+            // drop whatever half an answer came in, so it isn't read as keys. An answer that
+            // comes even later still gets through, but the attributes are ignored by crossterm
+            // SAFETY: the fd belongs to `tty`, which is open
+            unsafe { libc::tcflush(tty.as_raw_fd(), libc::TCIFLUSH) };
+            return None;
+        }
+        let mut fd = libc::pollfd {
+            fd: tty.as_raw_fd(),
+            events: libc::POLLIN,
+            revents: 0,
+        };
+        let timeout = i32::try_from(left.as_millis().max(1)).unwrap_or(i32::MAX);
+        // SAFETY: `fd` is a single valid pollfd that lives through the call
+        if unsafe { libc::poll(&raw mut fd, 1, timeout) } <= 0 {
+            continue;
+        }
+        let mut byte = [0];
+        if tty.read(&mut byte).ok()? == 0 {
+            return None;
+        }
+        reply.push(byte[0]);
+        let text = String::from_utf8_lossy(&reply);
+        if byte[0] == b'c' && device_attributes(&text).is_some() {
+            break text.into_owned();
+        }
+    };
+    // the first attribute is the kind of terminal, the rest are what it can do
+    let sixel = device_attributes(&reply)?
+        .skip(1)
+        .any(|attribute| attribute == "4");
+    Some(Support {
+        kitty: reply.contains("\x1b_Gi=31;OK"),
+        sixel,
+    })
+}
+
+#[cfg(not(unix))]
+pub fn query() -> Option<Support> {
+    None
+}
+
+/// The attributes in a primary device attributes answer at the end of `reply`, like `ESC[?62;4c`.
+#[cfg(unix)]
+fn device_attributes(reply: &str) -> Option<impl Iterator<Item = &str>> {
+    let start = reply.rfind("\x1b[?")? + 3;
+    let attributes = reply[start..].strip_suffix('c')?;
+    attributes
+        .bytes()
+        .all(|b| b.is_ascii_digit() || b == b';')
+        .then(|| attributes.split(';'))
+}
+
+/// Size of a terminal cell in pixels.
+pub fn cell_size() -> (u16, u16) {
+    match window_size() {
+        Ok(size) if size.width > 0 && size.height > 0 && size.columns > 0 && size.rows > 0 => {
+            (size.width / size.columns, size.height / size.rows)
+        }
+        _ => FALLBACK_CELL_SIZE,
+    }
+}
+
+/// Encodes RGB pixel rows for the given protocol, scaled to fill `area`.
+/// Returns `None` for modes that draw with text instead.
+pub fn encode(mode: PreviewMode, lines: &[Vec<[u8; 3]>], area: Rect) -> Option<String> {
+    let height = lines.len();
+    let width = lines.first().map_or(0, Vec::len);
+    if width == 0 || height == 0 {
+        return None;
+    }
+    match mode {
+        PreviewMode::Kitty => {
+            let rgb: Vec<u8> = lines.iter().flatten().flatten().copied().collect();
+            Some(kitty(&rgb, width, height, area))
+        }
+        PreviewMode::ITerm2 => Some(iterm2(&png(lines), area)),
+        PreviewMode::Sixel => {
+            // sixel images are shown at their real size, so they're scaled up to the area here
+            let (cell_w, cell_h) = cell_size();
+            let target_w = usize::from(area.width) * usize::from(cell_w);
+            let target_h = usize::from(area.height) * usize::from(cell_h);
+            let scaled: Vec<Vec<[u8; 3]>> = (0..target_h)
+                .map(|y| {
+                    let line = &lines[y * height / target_h];
+                    (0..target_w).map(|x| line[x * width / target_w]).collect()
+                })
+                .collect();
+            Some(sixel(&scaled))
+        }
+        PreviewMode::HalfBlocks
+        | PreviewMode::Indexed256
+        | PreviewMode::Ansi16
+        | PreviewMode::Braille
+        | PreviewMode::Ascii => None,
+    }
+}
+
+/// Kitty graphics protocol, raw RGB sent in chunks and stretched over the cells in `area`.
+fn kitty(rgb: &[u8], width: usize, height: usize, area: Rect) -> String {
+    let data = base64(rgb);
+    let chunks: Vec<&[u8]> = data.as_bytes().chunks(4096).collect();
+    let mut out = String::new();
+    for (i, chunk) in chunks.iter().enumerate() {
+        let more = u8::from(i + 1 < chunks.len());
+        let chunk = String::from_utf8_lossy(chunk);
+        if i == 0 {
+            let _ = write!(
+                out,
+                "\x1b_Ga=T,f=24,i={KITTY_IMAGE_ID},s={width},v={height},c={},r={},C=1,q=2,m={more};{chunk}\x1b\\",
+                area.width, area.height
+            );
+        } else {
+            let _ = write!(out, "\x1b_Gm={more};{chunk}\x1b\\");
+        }
+    }
+    out
+}
+
+/// iTerm2 inline image protocol, takes a whole image file (PNG here).
+fn iterm2(png: &[u8], area: Rect) -> String {
+    format!(
+        "\x1b]1337;File=inline=1;size={};width={};height={};preserveAspectRatio=0:{}\x07",
+        png.len(),
+        area.width,
+        area.height,
+        base64(png)
+    )
+}
+
+/// Sixel, using a 6x6x6 color cube for the palette.
+fn sixel(lines: &[Vec<[u8; 3]>]) -> String {
+    let height = lines.len();
+    let width = lines.first().map_or(0, Vec::len);
+    let level = |c: u8| usize::from(c) * 6 / 256;
+    let index = |p: [u8; 3]| level(p[0]) * 36 + level(p[1]) * 6 + level(p[2]);
+
+    let mut out = format!("\x1bPq\"1;1;{width};{height}");
+    for i in 0..216 {
+        let _ = write!(
+            out,
+            "#{i};2;{};{};{}",
+            i / 36 * 20,
+            i / 6 % 6 * 20,
+            i % 6 * 20
+        );
+    }
+
+    for band in lines.chunks(6) {
+        let mut used = [false; 216];
+        for pixel in band.iter().flatten() {
+            used[index(*pixel)] = true;
+        }
+        for color in (0..216).filter(|&c| used[c]) {
+            let _ = write!(out, "#{color}");
+            let mut run: Option<(u8, usize)> = None;
+            for x in 0..width {
+                let mut bits = 0u8;
+                for (bit, line) in band.iter().enumerate() {
+                    if index(line[x]) == color {
+                        bits |= 1 << bit;
+                    }
+                }
+                let symbol = 63 + bits;
+                run = match run {
+                    Some((s, n)) if s == symbol => Some((s, n + 1)),
+                    Some((s, n)) => {
+                        push_sixel_run(&mut out, s, n);
+                        Some((symbol, 1))
+                    }
+                    None => Some((symbol, 1)),
+                };
+            }
+            if let Some((s, n)) = run {
+                push_sixel_run(&mut out, s, n);
+            }
+            out.push('$');
+        }
+        out.push('-');
+    }
+    out.push_str("\x1b\\");
+    out
+}
+
+fn push_sixel_run(out: &mut String, symbol: u8, count: usize) {
+    if count > 3 {
+        let _ = write!(out, "!{count}{}", char::from(symbol));
+    } else {
+        for _ in 0..count {
+            out.push(char::from(symbol));
+        }
+    }
+}
+
+fn base64(data: &[u8]) -> String {
+    const TABLE: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut out = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let b = [chunk[0], *chunk.get(1).unwrap_or(&0), *chunk.get(2).unwrap_or(&0)];
+        let n = u32::from(b[0]) << 16 | u32::from(b[1]) << 8 | u32::from(b[2]);
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(char::from(TABLE[(n >> (18 - 6 * i) & 63) as usize]));
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+/// A minimal PNG encoder, pixels are stored uncompressed since it's only going to the terminal.
+fn png(lines: &[Vec<[u8; 3]>]) -> Vec<u8> {
+    let height = u32::try_from(lines.len()).unwrap_or(u32::MAX);
+    let width = u32::try_from(lines.first().map_or(0, Vec::len)).unwrap_or(u32::MAX);
+
+    // every row starts with filter type 0 (none)
+    let mut raw = Vec::new();
+    for line in lines {
+        raw.push(0);
+        raw.extend(line.iter().flatten());
+    }
+
+    let mut zlib = vec![0x78, 0x01];
+    let blocks: Vec<&[u8]> = raw.chunks(0xffff).collect();
+    for (i, block) in blocks.iter().enumerate() {
+        let len = u16::try_from(block.len()).unwrap_or(u16::MAX);
+        zlib.push(u8::from(i + 1 == blocks.len()));
+        zlib.extend(len.to_le_bytes());
+        zlib.extend((!len).to_le_bytes());
+        zlib.extend(*block);
+    }
+    zlib.extend(adler32(&raw).to_be_bytes());
+
+    let mut header = Vec::new();
+    header.extend(width.to_be_bytes());
+    header.extend(height.to_be_bytes());
+    header.extend([8, 2, 0, 0, 0]); // 8 bit RGB
+
+    let mut out = vec![0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n'];
+    for (kind, data) in [(b"IHDR", header), (b"IDAT", zlib), (b"IEND", Vec::new())] {
+        out.extend(u32::try_from(data.len()).unwrap_or(u32::MAX).to_be_bytes());
+        let start = out.len();
+        out.extend(kind);
+        out.extend(&data);
+        let crc = crc32(&out[start..]);
+        out.extend(crc.to_be_bytes());
+    }
+    out
+}
+
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = 0xffff_ffff_u32;
+    for &byte in data {
+        crc ^= u32::from(byte);
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 {
+                0xedb8_8320 ^ (crc >> 1)
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+fn adler32(data: &[u8]) -> u32 {
+    let (mut a, mut b) = (1u32, 0u32);
+    for &byte in data {
+        a = (a + u32::from(byte)) % 65521;
+        b = (b + a) % 65521;
+    }
+    b << 16 | a
+}
@@ -0,0 +1,165 @@
+#![warn(clippy::pedantic)]
+//! Groups of objects that move, scale and turn together. An object in a group keeps its position
+//! relative to the group, and the group's transform places it in the scene when the world is
+//! built. Groups can be nested, each one is placed by the group it's in.
+
+use rtwlib::vec3::{Point3, Vec3};
+
+use crate::app::{plane_height, ObjectType, SceneObject};
+
+#[derive(Clone)]
+pub struct SceneGroup {
+    pub name: String,
+    /// The group this one is in, an index into `App::groups`
+    pub parent: Option<usize>,
+    /// Point the group scales and turns around, relative to the group it's in
+    pub pivot: Point3,
+    /// How far the pivot is moved
+    pub offset: Vec3,
+    pub scale: f64,
+    /// Degrees around the vertical axis through the pivot, counterclockwise seen from above
+    pub turn: f64,
+    /// Whether the table hides the group's contents
+    pub collapsed: bool,
+}
+
+impl SceneGroup {
+    pub fn new(name: String, parent: Option<usize>, pivot: Point3) -> Self {
+        Self {
+            name,
+            parent,
+            pivot,
+            offset: Vec3::new(0., 0., 0.),
+            scale: 1.0,
+            turn: 0.0,
+            collapsed: false,
+        }
+    }
+
+    /// The group's name, or "Group" and its `index` if it hasn't been given one.
+    pub fn label(&self, index: usize) -> String {
+        if self.name.is_empty() {
+            format!("Group {index}")
+        } else {
+            self.name.clone()
+        }
+    }
+
+    /// Where the group moves a direction, which is turned and scaled but not moved.
+    fn transform_vector(&self, v: Vec3) -> Vec3 {
+        let (sin, cos) = self.turn.to_radians().sin_cos();
+        Vec3::new(v.x * cos + v.z * sin, v.y, v.z * cos - v.x * sin) * self.scale
+    }
+
+    /// The direction that [`SceneGroup::transform_vector`] moves to `v`.
+    pub fn inverse_vector(&self, v: Vec3) -> Vec3 {
+        let (sin, cos) = (-self.turn).to_radians().sin_cos();
+        Vec3::new(v.x * cos + v.z * sin, v.y, v.z * cos - v.x * sin) * (1.0 / self.scale)
+    }
+
+    /// Where the group moves a point from inside it to the group it's in.
+    pub fn transform_point(&self, point: Point3) -> Point3 {
+        self.pivot + self.offset + self.transform_vector(point - self.pivot)
+    }
+
+    /// Moves an object from inside the group to the group it's in.
+    pub fn transform(&self, object: &SceneObject) -> SceneObject {
+        let mut placed = object.clone();
+        match object.kind {
+            ObjectType::Sphere => {
+                placed.position = self.transform_point(object.position);
+                placed.size = object.size * self.scale;
+            }
+            ObjectType::Plane => {
+                let through = self.transform_point(Point3::new(0., object.size, 0.));
+                placed.position = self.transform_vector(object.position);
+                placed.size = plane_height(placed.position, through).unwrap_or(object.size);
+            }
+        }
+        placed
+    }
+
+    /// Where a point from the group it's in ends up inside the group, undoing
+    /// [`SceneGroup::transform_point`].
+    pub fn inverse_point(&self, point: Point3) -> Point3 {
+        self.pivot + self.inverse_vector(point - self.pivot - self.offset)
+    }
+
+    /// Moves an object from the group it's in to inside the group, undoing
+    /// [`SceneGroup::transform`].
+    pub fn inverse_transform(&self, object: &SceneObject) -> SceneObject {
+        let mut local = object.clone();
+        match object.kind {
+            ObjectType::Sphere => {
+                local.position = self.inverse_point(object.position);
+                local.size = object.size / self.scale;
+            }
+            ObjectType::Plane => {
+                let through = self.inverse_point(Point3::new(0., object.size, 0.));
+                local.position = self.inverse_vector(object.position);
+                local.size = plane_height(local.position, through).unwrap_or(object.size);
+            }
+        }
+        local
+    }
+
+    /// `child` with this group's transform folded into it, for when this group is taken away.
+    pub fn absorb(&self, child: &SceneGroup) -> SceneGroup {
+        SceneGroup {
+            parent: self.parent,
+            offset: self.transform_point(child.pivot + child.offset) - child.pivot,
+            scale: self.scale * child.scale,
+            turn: self.turn + child.turn,
+            ..child.clone()
+        }
+    }
+}
+
+/// A row in the object table.
+#[derive(Clone, Copy, PartialEq)]
+pub enum TableRow {
+    Group(usize),
+    Object(usize),
+}
+
+/// The rows of the object table, with how deeply each is nested. Groups come first, each followed
+/// by what's in it unless it's collapsed, then the objects that aren't in a group.
+pub fn table_rows(objects: &[SceneObject], groups: &[SceneGroup]) -> Vec<(TableRow, usize)> {
+    fn add(
+        rows: &mut Vec<(TableRow, usize)>,
+        objects: &[SceneObject],
+        groups: &[SceneGroup],
+        parent: Option<usize>,
+        depth: usize,
+    ) {
+        for (g, group) in groups.iter().enumerate() {
+            if group.parent != parent {
+                continue;
+            }
+            rows.push((TableRow::Group(g), depth));
+            if !group.collapsed {
+                add(rows, objects, groups, Some(g), depth + 1);
+            }
+        }
+        for (i, object) in objects.iter().enumerate() {
+            if object.group == parent {
+                rows.push((TableRow::Object(i), depth));
+            }
+        }
+    }
+    let mut rows = Vec::new();
+    add(&mut rows, objects, groups, None, 0);
+    rows
+}
+
+/// Whether `group` is `ancestor` or somewhere inside it.
+pub fn is_within(groups: &[SceneGroup], group: Option<usize>, ancestor: usize) -> bool {
+    let mut current = group;
+    while let Some(g) = current {
+        if g == ancestor {
+            return true;
+        }
+        current = groups[g].parent;
+    }
+    false
+}
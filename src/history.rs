@@ -0,0 +1,50 @@
+#![warn(clippy::pedantic)]
+//! Undo and redo for changes to the objects and groups in the scene. Scenes are small, so each
+//! step keeps a whole copy of the object list rather than working out what changed.
+
+use std::collections::BTreeSet;
+
+use crate::{app::SceneObject, group::SceneGroup};
+
+/// How many changes can be undone.
+const LIMIT: usize = 100;
+
+/// The objects and groups as they were at one point, and which ones were selected.
+pub struct Snapshot {
+    pub objects: Vec<SceneObject>,
+    pub groups: Vec<SceneGroup>,
+    pub selected: Option<usize>,
+    pub selected_group: Option<usize>,
+    pub marked: BTreeSet<usize>,
+}
+
+#[derive(Default)]
+pub struct History {
+    undo: Vec<Snapshot>,
+    redo: Vec<Snapshot>,
+}
+
+impl History {
+    /// Remembers the scene from before a change. Whatever was undone can't be redone after this.
+    pub fn push(&mut self, before: Snapshot) {
+        self.undo.push(before);
+        if self.undo.len() > LIMIT {
+            self.undo.remove(0);
+        }
+        self.redo.clear();
+    }
+
+    /// Trades `current` for the scene before the last change, if there was one.
+    pub fn undo(&mut self, current: Snapshot) -> Option<Snapshot> {
+        let previous = self.undo.pop()?;
+        self.redo.push(current);
+        Some(previous)
+    }
+
+    /// Trades `current` for the scene the last undo went back from, if there was one.
+    pub fn redo(&mut self, current: Snapshot) -> Option<Snapshot> {
+        let next = self.redo.pop()?;
+        self.undo.push(current);
+        Some(next)
+    }
+}
@@ -0,0 +1,314 @@
+#![warn(clippy::pedantic)]
+//! A single line text field with a cursor and selection, used by every editor.
+
+use std::ops::{Deref, Range, RangeInclusive};
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::{
+    style::{Modifier, Style},
+    text::{Line, Span},
+};
+
+/// Limits for a field that only takes numbers.
+#[derive(Clone, Copy)]
+struct Numeric {
+    min: f64,
+    max: f64,
+    /// How much the arrow keys and scroll wheel change the value, a tenth of this with shift
+    step: f64,
+    integer: bool,
+}
+
+#[derive(Default)]
+pub struct TextInput {
+    value: String,
+    /// Byte index of the cursor, always on a char boundary
+    cursor: usize,
+    /// Where the selection started, it runs from here to the cursor
+    anchor: Option<usize>,
+    numeric: Option<Numeric>,
+}
+
+impl From<&str> for TextInput {
+    fn from(value: &str) -> Self {
+        Self {
+            value: value.to_string(),
+            cursor: value.len(),
+            anchor: None,
+            numeric: None,
+        }
+    }
+}
+
+impl Deref for TextInput {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.value
+    }
+}
+
+impl std::fmt::Display for TextInput {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.value)
+    }
+}
+
+/// Number of digits after the decimal point.
+fn decimals(text: &str) -> usize {
+    text.split_once('.').map_or(0, |(_, digits)| digits.len())
+}
+
+impl TextInput {
+    /// A field that only takes decimal numbers in `range`.
+    pub fn number(value: &str, range: RangeInclusive<f64>, step: f64) -> Self {
+        Self {
+            numeric: Some(Numeric {
+                min: *range.start(),
+                max: *range.end(),
+                step,
+                integer: false,
+            }),
+            ..Self::from(value)
+        }
+    }
+
+    /// A field that only takes whole numbers in `range`.
+    pub fn integer(value: &str, range: RangeInclusive<i32>, step: i32) -> Self {
+        Self {
+            numeric: Some(Numeric {
+                min: f64::from(*range.start()),
+                max: f64::from(*range.end()),
+                step: f64::from(step),
+                integer: true,
+            }),
+            ..Self::from(value)
+        }
+    }
+
+    /// Whether the field only takes numbers, which the arrow keys step.
+    pub fn is_numeric(&self) -> bool {
+        self.numeric.is_some()
+    }
+
+    /// Whether `text` could be typed into the field, numbers can still be half finished.
+    fn accepts(&self, text: &str) -> bool {
+        let Some(numeric) = self.numeric else {
+            return true;
+        };
+        let digits = match text.strip_prefix('-') {
+            Some(rest) if numeric.min < 0.0 => rest,
+            _ => text,
+        };
+        digits
+            .chars()
+            .all(|c| c.is_ascii_digit() || (c == '.' && !numeric.integer))
+            && digits.matches('.').count() <= 1
+    }
+
+    /// Brings a number back into range. Done when the field loses focus rather than while typing,
+    /// since the digits on the way to a valid number can be out of range.
+    pub fn clamp(&mut self) {
+        let Some(numeric) = self.numeric else {
+            return;
+        };
+        if let Ok(value) = self.value.parse::<f64>() {
+            if value < numeric.min || value > numeric.max {
+                self.set(value.clamp(numeric.min, numeric.max).to_string());
+            }
+        }
+    }
+
+    /// Adds or takes away a step from a number, or a tenth of a step if `fine` is set.
+    /// Returns false if the field doesn't take numbers.
+    pub fn step(&mut self, up: bool, fine: bool) -> bool {
+        let Some(numeric) = self.numeric else {
+            return false;
+        };
+        let step = match (fine, numeric.integer) {
+            (false, _) => numeric.step,
+            (true, false) => numeric.step / 10.0,
+            (true, true) => (numeric.step / 10.0).round().max(1.0),
+        };
+        let current = self.value.parse::<f64>().unwrap_or(0.0);
+        let value =
+            if up { current + step } else { current - step }.clamp(numeric.min, numeric.max);
+        // keep any precision that was typed in, but round away the float error from stepping
+        let places = if numeric.integer {
+            0
+        } else {
+            decimals(&self.value).max(decimals(&step.to_string()))
+        };
+        self.set(format!("{value:.places$}"));
+        true
+    }
+
+    /// Replaces the text, leaving the cursor at the end.
+    pub fn set(&mut self, value: impl Into<String>) {
+        self.value = value.into();
+        self.cursor = self.value.len();
+        self.anchor = None;
+    }
+
+    fn selection(&self) -> Option<Range<usize>> {
+        let anchor = self.anchor.filter(|&anchor| anchor != self.cursor)?;
+        Some(anchor.min(self.cursor)..anchor.max(self.cursor))
+    }
+
+    /// Types `text` at the cursor, replacing the selection. Returns false if it was rejected, like
+    /// letters in a number field.
+    pub fn insert(&mut self, text: &str) -> bool {
+        let range = self.selection().unwrap_or(self.cursor..self.cursor);
+        let mut value = self.value.clone();
+        value.replace_range(range.clone(), text);
+        if !self.accepts(&value) {
+            return false;
+        }
+        self.value = value;
+        self.anchor = None;
+        self.cursor = range.start + text.len();
+        true
+    }
+
+    fn prev_char(&self, from: usize) -> usize {
+        self.value[..from]
+            .char_indices()
+            .next_back()
+            .map_or(0, |(i, _)| i)
+    }
+
+    fn next_char(&self, from: usize) -> usize {
+        self.value[from..]
+            .chars()
+            .next()
+            .map_or(from, |c| from + c.len_utf8())
+    }
+
+    /// Start of the word before `from`, skipping any spaces in between.
+    fn prev_word(&self, from: usize) -> usize {
+        let before = self.value[..from].trim_end();
+        before
+            .rfind(|c: char| !c.is_alphanumeric() && c != '.')
+            .map_or(0, |i| self.next_char(i))
+            .min(before.len())
+    }
+
+    /// End of the word after `from`, skipping any spaces in between.
+    fn next_word(&self, from: usize) -> usize {
+        let after = &self.value[from..];
+        let skipped = after.len() - after.trim_start().len();
+        let rest = &after[skipped..];
+        from + skipped
+            + rest
+                .find(|c: char| !c.is_alphanumeric() && c != '.')
+                .unwrap_or(rest.len())
+    }
+
+    /// Moves the cursor, extending the selection if `select` is set.
+    fn move_to(&mut self, to: usize, select: bool) {
+        if select {
+            self.anchor.get_or_insert(self.cursor);
+        } else {
+            self.anchor = None;
+        }
+        self.cursor = to;
+    }
+
+    /// Deletes from the cursor to `to`, or the selection if there is one.
+    fn delete_to(&mut self, to: usize) {
+        let range = self
+            .selection()
+            .unwrap_or(to.min(self.cursor)..to.max(self.cursor));
+        self.anchor = None;
+        self.cursor = range.start;
+        self.value.replace_range(range, "");
+    }
+
+    /// Edits the text for a key press. Returns false if the key wasn't used, which includes moving
+    /// left from the start or right from the end, so the caller can move to another field.
+    /// Numbers are clamped when a key isn't used, since it usually submits or moves focus.
+    pub fn handle_key(&mut self, key: KeyEvent) -> bool {
+        let used = self.edit(key);
+        if !used {
+            self.clamp();
+        }
+        used
+    }
+
+    fn edit(&mut self, key: KeyEvent) -> bool {
+        let ctrl = key
+            .modifiers
+            .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT);
+        let shift = key.modifiers.contains(KeyModifiers::SHIFT);
+        match key.code {
+            KeyCode::Char('a') if ctrl => {
+                self.anchor = Some(0);
+                self.cursor = self.value.len();
+            }
+            KeyCode::Char('w') | KeyCode::Backspace if ctrl => {
+                self.delete_to(self.prev_word(self.cursor));
+            }
+            KeyCode::Char(_) if ctrl => return false,
+            // a rejected char is still used up, so it doesn't trigger anything else
+            KeyCode::Char(c) => {
+                self.insert(c.encode_utf8(&mut [0; 4]));
+            }
+            KeyCode::Up | KeyCode::Down if self.numeric.is_some() => {
+                self.step(key.code == KeyCode::Up, shift);
+            }
+            KeyCode::Backspace => self.delete_to(self.prev_char(self.cursor)),
+            KeyCode::Delete if ctrl => self.delete_to(self.next_word(self.cursor)),
+            KeyCode::Delete => self.delete_to(self.next_char(self.cursor)),
+            KeyCode::Home => self.move_to(0, shift),
+            KeyCode::End => self.move_to(self.value.len(), shift),
+            KeyCode::Left if self.cursor == 0 && self.selection().is_none() && !shift => {
+                return false
+            }
+            KeyCode::Right
+                if self.cursor == self.value.len() && self.selection().is_none() && !shift =>
+            {
+                return false
+            }
+            KeyCode::Left if ctrl => self.move_to(self.prev_word(self.cursor), shift),
+            KeyCode::Right if ctrl => self.move_to(self.next_word(self.cursor), shift),
+            // without shift, the arrows drop the selection on the side they point to
+            KeyCode::Left => match self.selection().filter(|_| !shift) {
+                Some(range) => self.move_to(range.start, false),
+                None => self.move_to(self.prev_char(self.cursor), shift),
+            },
+            KeyCode::Right => match self.selection().filter(|_| !shift) {
+                Some(range) => self.move_to(range.end, false),
+                None => self.move_to(self.next_char(self.cursor), shift),
+            },
+            _ => return false,
+        }
+        true
+    }
+
+    /// The text as a line, with the caret and selection shown if the field is focused. Selected
+    /// text is drawn with `selected`.
+    pub fn line(&self, focused: bool, selected: Style) -> Line<'_> {
+        if !focused {
+            return Line::raw(self.value.as_str());
+        }
+        let caret = Style::default().add_modifier(Modifier::REVERSED);
+        let range = self.selection().unwrap_or(self.cursor..self.cursor);
+
+        let mut spans = vec![Span::raw(&self.value[..range.start])];
+        spans.push(Span::styled(&self.value[range.clone()], selected));
+        let rest = &self.value[range.end..];
+        if range.end == self.cursor {
+            // the caret sits on the next char, or past the end of the text
+            let next = self.next_char(self.cursor) - self.cursor;
+            if next == 0 {
+                spans.push(Span::styled(" ", caret));
+            } else {
+                spans.push(Span::styled(&rest[..next], caret));
+                spans.push(Span::raw(&rest[next..]));
+            }
+        } else {
+            spans.push(Span::raw(rest));
+        }
+        Line::from(spans)
+    }
+}
@@ -0,0 +1,166 @@
+#![warn(clippy::pedantic)]
+//! Shows every setting of the selected object and its material, under the object table.
+
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, BorderType, Borders, Paragraph},
+    Frame,
+};
+use rtwlib::vec3::Vec3;
+
+use crate::{
+    app::{shown, MaterialType, ObjectType},
+    group::TableRow,
+    App,
+};
+
+fn vector(v: Vec3) -> String {
+    format!("{:.3}, {:.3}, {:.3}", v.x, v.y, v.z)
+}
+
+/// A color as hex, the way colors are typed into the editors.
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn hex(color: Vec3) -> String {
+    let byte = |c: f64| (c.clamp(0.0, 1.0) * 255.0).round() as u8;
+    format!("{:02x}{:02x}{:02x}", byte(color.x), byte(color.y), byte(color.z))
+}
+
+fn heading(app: &App, text: &str) -> Line<'static> {
+    Line::styled(
+        text.to_string(),
+        Style::default()
+            .fg(app.theme.heading)
+            .add_modifier(Modifier::BOLD),
+    )
+}
+
+fn field(app: &App, name: &str, value: String) -> Line<'static> {
+    Line::from(vec![
+        Span::styled(format!("{name}: "), Style::default().fg(app.theme.label)),
+        Span::raw(value),
+    ])
+}
+
+/// The inspector's border, with how many objects are marked.
+fn block(app: &App, title: String) -> Block<'static> {
+    Block::default()
+        .title(title)
+        .title(match app.marked.len() {
+            0 => String::new(),
+            marked => format!("[{marked} selected]"),
+        })
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+}
+
+/// Draws the two columns of lines inside the inspector's border.
+fn render_columns(frame: &mut Frame, area: Rect, block: Block, left: Vec<Line>, right: Vec<Line>) {
+    let columns = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+        .split(block.inner(area));
+    frame.render_widget(block, area);
+    frame.render_widget(Paragraph::new(left), columns[0]);
+    frame.render_widget(Paragraph::new(right), columns[1]);
+}
+
+pub fn render_inspector(frame: &mut Frame, area: Rect, app: &App) {
+    match app.cursor() {
+        Some(TableRow::Object(index)) => render_object(frame, area, app, index),
+        Some(TableRow::Group(g)) => render_group(frame, area, app, g),
+        None => {
+            let block = Block::default()
+                .title("Inspector")
+                .borders(Borders::ALL)
+                .border_type(BorderType::Rounded);
+            let hint =
+                Paragraph::new("Nothing selected").style(Style::default().fg(app.theme.muted));
+            frame.render_widget(hint.block(block), area);
+        }
+    }
+}
+
+fn render_group(frame: &mut Frame, area: Rect, app: &App, g: usize) {
+    let group = &app.groups[g];
+    let mut transform = vec![heading(app, "Transform")];
+    transform.push(field(app, "Move", vector(group.offset)));
+    transform.push(field(app, "Scale", format!("{:.3}", group.scale)));
+    transform.push(field(app, "Turn", format!("{:.1}°", group.turn)));
+    transform.push(field(app, "Pivot", vector(group.pivot)));
+
+    let mut contents = vec![heading(app, "Contents")];
+    let groups = app.groups.iter().filter(|other| other.parent == Some(g));
+    contents.push(field(app, "Groups", groups.count().to_string()));
+    contents.push(field(
+        app,
+        "Objects",
+        app.group_members(g).len().to_string(),
+    ));
+    let parent = group
+        .parent
+        .map_or_else(|| String::from("None"), |p| app.groups[p].label(p));
+    contents.push(field(app, "In group", parent));
+
+    let block = block(app, format!("Inspector: {}", group.label(g)));
+    render_columns(frame, area, block, transform, contents);
+}
+
+fn render_object(frame: &mut Frame, area: Rect, app: &App, index: usize) {
+    let object = &app.objects[index];
+    let block = block(app, format!("Inspector: {}", object.label(index)));
+    let heading = |text: &str| heading(app, text);
+    let field = |name: &str, value: String| field(app, name, value);
+
+    let mut shape = vec![heading("Shape")];
+    match object.kind {
+        ObjectType::Sphere => {
+            shape.push(field("Center", vector(object.position)));
+            shape.push(field("Radius", format!("{:.3}", object.size)));
+        }
+        ObjectType::Plane => {
+            shape.push(field("Normal", vector(object.position)));
+            shape.push(field("Height", format!("{:.3}", object.size)));
+        }
+    }
+
+    let visible = if object.solo {
+        "Solo"
+    } else if object.hidden {
+        "Hidden"
+    } else if shown(&app.objects).contains(&index) {
+        "Yes"
+    } else {
+        "No, another object is soloed"
+    };
+    shape.push(field("Visible", visible.to_string()));
+    // positions in a group are relative to it
+    if let Some(g) = object.group {
+        shape.push(field("Group", app.groups[g].label(g)));
+    }
+
+    let mut material = vec![heading("Material")];
+    match app.materials.get(object.material) {
+        Some(mat) => {
+            material.push(field("Name", mat.name.clone()));
+            material.push(field("Type", mat.kind.to_string()));
+            match mat.kind {
+                MaterialType::Lambertian => {
+                    material.push(field("Color", hex(mat.color)));
+                }
+                MaterialType::Metal => {
+                    material.push(field("Color", hex(mat.color)));
+                    material.push(field("Fuzz", format!("{:.3}", mat.other)));
+                }
+                MaterialType::Dielectric => {
+                    material.push(field("Index of refraction", format!("{:.3}", mat.other)));
+                }
+                MaterialType::Normal => {}
+            }
+        }
+        None => material.push(Line::raw("Missing material")),
+    }
+
+    render_columns(frame, area, block, shape, material);
+}
@@ -0,0 +1,371 @@
+#![warn(clippy::pedantic)]
+//! Key bindings. Every action has default keys, which can be changed in `keys.json` in the config
+//! dir. The file maps action names to a key or a list of keys, for example
+//! `{ "new_object": "a", "quit": ["q", "ctrl+c"] }`. Actions it leaves out keep their defaults.
+//!
+//! Text fields, moving between fields, Enter and Esc aren't bound here, they work the same on
+//! every screen.
+
+use std::{collections::HashMap, fs};
+
+use color_eyre::{
+    eyre::{bail, eyre, WrapErr},
+    Result,
+};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+use crate::config::config_dir;
+
+/// The screen an action is used on. A key can mean different things in different sections.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Section {
+    Main,
+    Render,
+    Preview,
+    Viewer,
+    /// The screens with forms, for keys that aren't typed into the fields
+    Editor,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Action {
+    SelectPrevious,
+    SelectNext,
+    ToggleMarked,
+    ExtendUp,
+    ExtendDown,
+    MarkAll,
+    BulkEdit,
+    DeleteObject,
+    DuplicateObject,
+    ArrayObject,
+    RenameObject,
+    ToggleHidden,
+    SoloObject,
+    GroupObjects,
+    Ungroup,
+    CollapseGroup,
+    ExpandGroup,
+    Undo,
+    Redo,
+    NewObject,
+    NewMaterial,
+    Preview,
+    EditSky,
+    FocusSelected,
+    OpenViewer,
+    ToggleMap,
+    ToggleSplit,
+    Render,
+    Quit,
+    AutoFocus,
+    PreviewFullScreen,
+    GraphicsMode,
+    Shading,
+    MoreSamples,
+    FewerSamples,
+    MoreBounces,
+    FewerBounces,
+    LowerResolution,
+    HigherResolution,
+    ZoomIn,
+    ZoomOut,
+    ResetView,
+    ViewerFullScreen,
+    PanUp,
+    PanDown,
+    PanLeft,
+    PanRight,
+    ColorPicker,
+}
+
+struct ActionInfo {
+    action: Action,
+    /// Name used in the config file
+    name: &'static str,
+    section: Section,
+    /// Shown next to the keys in the sidebar
+    help: &'static str,
+    defaults: &'static [&'static str],
+}
+
+/// Every action, in the order the sidebar lists them.
+#[rustfmt::skip]
+const ACTIONS: &[ActionInfo] = &[
+    ActionInfo { action: Action::SelectPrevious, name: "select_previous", section: Section::Main, help: "Select the previous object", defaults: &["up"] },
+    ActionInfo { action: Action::SelectNext, name: "select_next", section: Section::Main, help: "Select the next object", defaults: &["down"] },
+    ActionInfo { action: Action::ToggleMarked, name: "toggle_marked", section: Section::Main, help: "Add/remove object from the selection", defaults: &["space"] },
+    ActionInfo { action: Action::ExtendUp, name: "extend_up", section: Section::Main, help: "Extend the selection up", defaults: &["shift+up"] },
+    ActionInfo { action: Action::ExtendDown, name: "extend_down", section: Section::Main, help: "Extend the selection down", defaults: &["shift+down"] },
+    ActionInfo { action: Action::MarkAll, name: "mark_all", section: Section::Main, help: "Select all/none", defaults: &["ctrl+a"] },
+    ActionInfo { action: Action::BulkEdit, name: "bulk_edit", section: Section::Main, help: "Move, scale or change material of selection, or edit a group", defaults: &["x"] },
+    ActionInfo { action: Action::DeleteObject, name: "delete_object", section: Section::Main, help: "Delete selected objects", defaults: &["d"] },
+    ActionInfo { action: Action::DuplicateObject, name: "duplicate_object", section: Section::Main, help: "Duplicate selected object", defaults: &["c"] },
+    ActionInfo { action: Action::ArrayObject, name: "array_object", section: Section::Main, help: "Copy selected object in a line, grid or circle", defaults: &["a"] },
+    ActionInfo { action: Action::RenameObject, name: "rename_object", section: Section::Main, help: "Rename selected object or group", defaults: &["e"] },
+    ActionInfo { action: Action::ToggleHidden, name: "toggle_hidden", section: Section::Main, help: "Hide/show selected objects", defaults: &["h"] },
+    ActionInfo { action: Action::SoloObject, name: "solo_object", section: Section::Main, help: "Solo selected object", defaults: &["o"] },
+    ActionInfo { action: Action::GroupObjects, name: "group_objects", section: Section::Main, help: "Group selected objects", defaults: &["g"] },
+    ActionInfo { action: Action::Ungroup, name: "ungroup", section: Section::Main, help: "Ungroup", defaults: &["shift+g"] },
+    ActionInfo { action: Action::CollapseGroup, name: "collapse_group", section: Section::Main, help: "Collapse group", defaults: &["left"] },
+    ActionInfo { action: Action::ExpandGroup, name: "expand_group", section: Section::Main, help: "Expand group", defaults: &["right"] },
+    ActionInfo { action: Action::Undo, name: "undo", section: Section::Main, help: "Undo a change to the objects", defaults: &["u", "ctrl+z"] },
+    ActionInfo { action: Action::Redo, name: "redo", section: Section::Main, help: "Redo", defaults: &["shift+u", "ctrl+y"] },
+    ActionInfo { action: Action::NewObject, name: "new_object", section: Section::Main, help: "Create a new object", defaults: &["n"] },
+    ActionInfo { action: Action::NewMaterial, name: "new_material", section: Section::Main, help: "Create a new material", defaults: &["m"] },
+    ActionInfo { action: Action::Preview, name: "preview", section: Section::Main, help: "View a preview render (ESC to close)", defaults: &["p"] },
+    ActionInfo { action: Action::EditSky, name: "edit_sky", section: Section::Main, help: "Edit the sky/background", defaults: &["b"] },
+    ActionInfo { action: Action::FocusSelected, name: "focus_selected", section: Section::Main, help: "Focus camera on selected object", defaults: &["f"] },
+    ActionInfo { action: Action::OpenViewer, name: "open_viewer", section: Section::Main, help: "View the last render", defaults: &["v"] },
+    ActionInfo { action: Action::ToggleMap, name: "toggle_map", section: Section::Main, help: "Toggle the scene map", defaults: &["t"] },
+    ActionInfo { action: Action::ToggleSplit, name: "toggle_split", section: Section::Main, help: "Toggle the split-screen preview", defaults: &["s"] },
+    ActionInfo { action: Action::Render, name: "render", section: Section::Main, help: "Render the scene", defaults: &["r"] },
+    ActionInfo { action: Action::Quit, name: "quit", section: Section::Main, help: "Quit", defaults: &["q", "esc"] },
+    ActionInfo { action: Action::AutoFocus, name: "auto_focus", section: Section::Render, help: "Auto-focus on selection", defaults: &["ctrl+f"] },
+    ActionInfo { action: Action::PreviewFullScreen, name: "preview_full_screen", section: Section::Preview, help: "Full Screen", defaults: &["f"] },
+    ActionInfo { action: Action::GraphicsMode, name: "graphics_mode", section: Section::Preview, help: "Switch graphics mode", defaults: &["g"] },
+    ActionInfo { action: Action::Shading, name: "shading", section: Section::Preview, help: "Switch shading (normals, depth, ...)", defaults: &["s"] },
+    ActionInfo { action: Action::MoreSamples, name: "more_samples", section: Section::Preview, help: "More samples", defaults: &["+", "="] },
+    ActionInfo { action: Action::FewerSamples, name: "fewer_samples", section: Section::Preview, help: "Fewer samples", defaults: &["-"] },
+    ActionInfo { action: Action::MoreBounces, name: "more_bounces", section: Section::Preview, help: "More bounces", defaults: &["]"] },
+    ActionInfo { action: Action::FewerBounces, name: "fewer_bounces", section: Section::Preview, help: "Fewer bounces", defaults: &["["] },
+    ActionInfo { action: Action::LowerResolution, name: "lower_resolution", section: Section::Preview, help: "Lower resolution", defaults: &["."] },
+    ActionInfo { action: Action::HigherResolution, name: "higher_resolution", section: Section::Preview, help: "Raise resolution", defaults: &[","] },
+    ActionInfo { action: Action::ZoomIn, name: "zoom_in", section: Section::Viewer, help: "Zoom in", defaults: &["+", "="] },
+    ActionInfo { action: Action::ZoomOut, name: "zoom_out", section: Section::Viewer, help: "Zoom out", defaults: &["-"] },
+    ActionInfo { action: Action::ResetView, name: "reset_view", section: Section::Viewer, help: "Reset view", defaults: &["0"] },
+    ActionInfo { action: Action::ViewerFullScreen, name: "viewer_full_screen", section: Section::Viewer, help: "Full Screen", defaults: &["f"] },
+    ActionInfo { action: Action::PanUp, name: "pan_up", section: Section::Viewer, help: "Pan up", defaults: &["up", "k"] },
+    ActionInfo { action: Action::PanDown, name: "pan_down", section: Section::Viewer, help: "Pan down", defaults: &["down", "j"] },
+    ActionInfo { action: Action::PanLeft, name: "pan_left", section: Section::Viewer, help: "Pan left", defaults: &["left", "h"] },
+    ActionInfo { action: Action::PanRight, name: "pan_right", section: Section::Viewer, help: "Pan right", defaults: &["right", "l"] },
+    ActionInfo { action: Action::ColorPicker, name: "color_picker", section: Section::Editor, help: "Pick a color for a color field", defaults: &["ctrl+p"] },
+];
+
+/// A key with its modifiers, as written in the config file, like `n`, `ctrl+f` or `pageup`.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct KeyBinding {
+    code: KeyCode,
+    modifiers: KeyModifiers,
+}
+
+impl KeyBinding {
+    pub fn parse(text: &str) -> Result<Self> {
+        // the last part is the key, which can be a `+` itself
+        let (prefix, key) = match text.strip_suffix("++") {
+            Some(prefix) => (Some(prefix), "+"),
+            None => match text.rsplit_once('+') {
+                Some((prefix, key)) if !key.is_empty() => (Some(prefix), key),
+                _ => (None, text),
+            },
+        };
+
+        let mut modifiers = KeyModifiers::NONE;
+        let modifier_names = prefix.into_iter().flat_map(|prefix| prefix.split('+'));
+        for modifier in modifier_names.filter(|name| !name.is_empty()) {
+            modifiers |= match modifier.to_lowercase().as_str() {
+                "ctrl" | "control" => KeyModifiers::CONTROL,
+                "alt" => KeyModifiers::ALT,
+                "shift" => KeyModifiers::SHIFT,
+                _ => bail!("unknown modifier `{modifier}` in `{text}`"),
+            };
+        }
+
+        let mut chars = key.chars();
+        let code = match (chars.next(), chars.next()) {
+            (Some(c), None) => KeyCode::Char(c),
+            _ => match key.to_lowercase().as_str() {
+                "esc" | "escape" => KeyCode::Esc,
+                "enter" | "return" => KeyCode::Enter,
+                "tab" => KeyCode::Tab,
+                "backtab" => KeyCode::BackTab,
+                "backspace" => KeyCode::Backspace,
+                "delete" | "del" => KeyCode::Delete,
+                "insert" => KeyCode::Insert,
+                "space" => KeyCode::Char(' '),
+                "up" => KeyCode::Up,
+                "down" => KeyCode::Down,
+                "left" => KeyCode::Left,
+                "right" => KeyCode::Right,
+                "home" => KeyCode::Home,
+                "end" => KeyCode::End,
+                "pageup" => KeyCode::PageUp,
+                "pagedown" => KeyCode::PageDown,
+                name => match name.strip_prefix('f').and_then(|n| n.parse().ok()) {
+                    Some(n @ 1..=12) => KeyCode::F(n),
+                    _ => bail!("unknown key `{key}` in `{text}`"),
+                },
+            },
+        };
+
+        // shift is part of the char for letters and symbols, `shift+n` is the same as `N`
+        if let KeyCode::Char(c) = code {
+            if modifiers.contains(KeyModifiers::SHIFT) {
+                modifiers.remove(KeyModifiers::SHIFT);
+                return Ok(Self {
+                    code: KeyCode::Char(c.to_ascii_uppercase()),
+                    modifiers,
+                });
+            }
+        }
+        Ok(Self { code, modifiers })
+    }
+
+    pub fn matches(&self, key: &KeyEvent) -> bool {
+        let mut modifiers = key.modifiers;
+        if let KeyCode::Char(_) = key.code {
+            modifiers.remove(KeyModifiers::SHIFT);
+        }
+        self.code == key.code && self.modifiers == modifiers
+    }
+}
+
+impl std::fmt::Display for KeyBinding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.modifiers.contains(KeyModifiers::CONTROL) {
+            write!(f, "Ctrl+")?;
+        }
+        if self.modifiers.contains(KeyModifiers::ALT) {
+            write!(f, "Alt+")?;
+        }
+        if self.modifiers.contains(KeyModifiers::SHIFT) {
+            write!(f, "Shift+")?;
+        }
+        match self.code {
+            // letters are shown in capitals, like on the keyboard
+            KeyCode::Char(c) if c.is_ascii_uppercase() => write!(f, "Shift+{c}"),
+            KeyCode::Char(' ') => write!(f, "Space"),
+            KeyCode::Char(c) => write!(f, "{}", c.to_ascii_uppercase()),
+            KeyCode::Up => write!(f, "↑"),
+            KeyCode::Down => write!(f, "↓"),
+            KeyCode::Left => write!(f, "←"),
+            KeyCode::Right => write!(f, "→"),
+            code => write!(f, "{code}"),
+        }
+    }
+}
+
+/// The active key bindings.
+pub struct Keymap {
+    bindings: HashMap<Action, Vec<KeyBinding>>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        let bindings = ACTIONS
+            .iter()
+            .map(|info| {
+                let keys = info
+                    .defaults
+                    .iter()
+                    .map(|key| KeyBinding::parse(key).expect("default keys should parse"))
+                    .collect();
+                (info.action, keys)
+            })
+            .collect();
+        Self { bindings }
+    }
+}
+
+impl Keymap {
+    /// Loads `keys.json` from the config dir on top of the defaults. A missing file is fine, but
+    /// one that can't be read, has unknown actions or keys, leaves an action without keys or binds
+    /// a key to two actions on the same screen is an error.
+    pub fn load() -> Result<Self> {
+        let mut keymap = Self::default();
+        let Some(path) = config_dir().map(|dir| dir.join("keys.json")) else {
+            return Ok(keymap);
+        };
+        if !path.exists() {
+            return Ok(keymap);
+        }
+        let text = fs::read_to_string(&path)
+            .wrap_err_with(|| format!("couldn't read {}", path.display()))?;
+        keymap
+            .apply(&text)
+            .wrap_err_with(|| format!("bad key bindings in {}", path.display()))?;
+        Ok(keymap)
+    }
+
+    fn apply(&mut self, text: &str) -> Result<()> {
+        let config: serde_json::Value = serde_json::from_str(text)?;
+        let entries = config
+            .as_object()
+            .ok_or_else(|| eyre!("expected an object of action names to keys"))?;
+        for (name, keys) in entries {
+            let info = ACTIONS
+                .iter()
+                .find(|info| info.name == name)
+                .ok_or_else(|| eyre!("unknown action `{name}`"))?;
+            let keys = match keys {
+                serde_json::Value::String(key) => vec![KeyBinding::parse(key)?],
+                serde_json::Value::Array(keys) => keys
+                    .iter()
+                    .map(|key| match key.as_str() {
+                        Some(key) => KeyBinding::parse(key),
+                        None => bail!("keys for `{name}` should be strings"),
+                    })
+                    .collect::<Result<_>>()?,
+                _ => bail!("`{name}` should be a key or a list of keys"),
+            };
+            if keys.is_empty() {
+                bail!("`{name}` needs at least one key");
+            }
+            self.bindings.insert(info.action, keys);
+        }
+
+        // a key can only do one thing on each screen, or the action listed later would never run
+        for (i, first) in ACTIONS.iter().enumerate() {
+            let first_keys = &self.bindings[&first.action];
+            for second in ACTIONS[i + 1..]
+                .iter()
+                .filter(|info| info.section == first.section)
+            {
+                let second_keys = &self.bindings[&second.action];
+                if let Some(key) = first_keys.iter().find(|key| second_keys.contains(key)) {
+                    bail!(
+                        "`{key}` is bound to both `{}` and `{}`",
+                        first.name,
+                        second.name
+                    );
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// The action bound to `key` in `section`, if there is one.
+    pub fn action(&self, section: Section, key: &KeyEvent) -> Option<Action> {
+        ACTIONS
+            .iter()
+            .filter(|info| info.section == section)
+            .map(|info| info.action)
+            .find(|action| {
+                self.bindings
+                    .get(action)
+                    .is_some_and(|keys| keys.iter().any(|binding| binding.matches(key)))
+            })
+    }
+
+    /// The keys for an action, as shown to the user, like `+/=`.
+    pub fn keys(&self, action: Action) -> String {
+        self.bindings
+            .get(&action)
+            .map(|keys| {
+                keys.iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<_>>()
+                    .join("/")
+            })
+            .unwrap_or_default()
+    }
+
+    /// Sidebar lines for a section's actions, like `  [N]: Create a new object`.
+    pub fn help(&self, section: Section) -> Vec<String> {
+        ACTIONS
+            .iter()
+            .filter(|info| info.section == section)
+            .map(|info| format!("  [{}]: {}", self.keys(info.action), info.help))
+            .collect()
+    }
+}
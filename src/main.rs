@@ -1,15 +1,38 @@
 #![allow(unused_imports)]
 #![warn(clippy::pedantic)]
 mod app;
+mod array;
+mod config;
+mod dither;
+mod form;
+mod graphics;
+mod group;
+mod history;
+mod input;
+mod inspector;
+mod keys;
+mod map;
+mod picker;
 mod preview;
+mod projection;
 mod render;
+mod theme;
 mod ui;
+mod viewer;
+mod worker;
 use app::*;
 use color_eyre::eyre::WrapErr;
 use color_eyre::Result;
-use crossterm::event::{self, DisableMouseCapture, Event, KeyCode, KeyEvent, KeyEventKind};
+use crossterm::event::{
+    self, DisableBracketedPaste, DisableMouseCapture, EnableBracketedPaste, Event, KeyCode,
+    KeyEvent, KeyEventKind, KeyModifiers, MouseButton, MouseEvent, MouseEventKind,
+};
 use crossterm::terminal::{disable_raw_mode, LeaveAlternateScreen};
+use group::TableRow;
+use input::TextInput;
+use keys::{Action, Keymap, Section};
 use preview::*;
+use projection::Projection;
 use ratatui::crossterm::event::EnableMouseCapture;
 use ratatui::crossterm::execute;
 use ratatui::crossterm::terminal::{enable_raw_mode, EnterAlternateScreen};
@@ -29,95 +52,303 @@ use render::*;
 use rtwlib::camera::*;
 use std::error::Error;
 use std::io::{self, stdout};
+use std::time::Duration;
 use std::result::Result::Ok;
+use theme::Theme;
 use ui::*;
 
 fn main() -> color_eyre::Result<()> {
     color_eyre::install()?;
+    // read the config before taking over the terminal, so errors are readable
+    let keys = Keymap::load()?;
+    let theme = Theme::load()?;
     // setup terminal
     enable_raw_mode()?;
+    // ask the terminal what it can draw before anything else reads its input
+    let preview_mode = PreviewMode::detect();
     ratatui::init();
-    execute!(stdout(), EnterAlternateScreen)?;
+    execute!(
+        stdout(),
+        EnterAlternateScreen,
+        EnableMouseCapture,
+        EnableBracketedPaste
+    )?;
 
     let mut terminal = ratatui::init();
+    // ratatui's panic hook restores the screen, but doesn't know about the mouse or pasting
+    let hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        let _ = execute!(stdout(), DisableMouseCapture, DisableBracketedPaste);
+        hook(info);
+    }));
 
     // create app and run it
     let mut app = App::new();
-    run_app(&mut terminal, &mut app)?;
-    // restore terminal
+    app.keys = keys;
+    app.theme = theme;
+    app.preview_mode = preview_mode;
+    let result = run_app(&mut terminal, &mut app);
+    // restore terminal, even if the app stopped on an error
+    execute!(stdout(), DisableMouseCapture, DisableBracketedPaste)?;
     ratatui::restore();
 
+    result.map(|_| ())
+}
+
+/// Draws the ui, then writes out any image the preview queued up.
+fn draw<B: Backend>(terminal: &mut Terminal<B>, app: &App) -> Result<()> {
+    terminal.draw(|f| ui(f, app))?;
+    if app.graphics.borrow_mut().flush()? {
+        terminal.clear()?;
+        terminal.draw(|f| ui(f, app))?;
+    }
     Ok(())
 }
 
+/// Selects whatever got clicked in the object table or the preview, and steps the focused number
+/// with the scroll wheel. Returns true if anything changed.
+fn handle_mouse(app: &mut App, mouse: MouseEvent) -> bool {
+    if let Some(picker) = &mut app.picker {
+        return match mouse.kind {
+            MouseEventKind::ScrollUp | MouseEventKind::ScrollDown => {
+                let fine = mouse.modifiers.contains(KeyModifiers::SHIFT);
+                picker.scroll(
+                    mouse.kind == MouseEventKind::ScrollUp,
+                    fine,
+                    &app.recent_colors,
+                );
+                true
+            }
+            MouseEventKind::Down(MouseButton::Left) => {
+                picker.click(mouse.column, mouse.row, &app.recent_colors)
+            }
+            _ => false,
+        };
+    }
+    if let MouseEventKind::ScrollUp | MouseEventKind::ScrollDown = mouse.kind {
+        let up = mouse.kind == MouseEventKind::ScrollUp;
+        let fine = mouse.modifiers.contains(KeyModifiers::SHIFT);
+        return edit_focused(app, |input| input.step(up, fine));
+    }
+    if mouse.kind != MouseEventKind::Down(MouseButton::Left) {
+        return false;
+    }
+    let picked = match app.current_screen {
+        CurrentScreen::Main => table_row_at(app, mouse.column, mouse.row),
+        // a camera that doesn't parse can't be clicked through
+        CurrentScreen::Preview | CurrentScreen::PreviewFull => pick(app, mouse.column, mouse.row)
+            .unwrap_or(None)
+            .map(TableRow::Object),
+        _ => return false,
+    };
+    if picked.is_none() {
+        return false;
+    }
+    // ctrl+click adds to the selection or takes the object out of it
+    if mouse.modifiers.contains(KeyModifiers::CONTROL) {
+        app.set_cursor(picked);
+        return app.toggle_marked();
+    }
+    if picked == app.cursor() {
+        return false;
+    }
+    app.set_cursor(picked);
+    true
+}
+
+/// Whether the current screen is an editor with text fields.
+fn on_editor(app: &App) -> bool {
+    matches!(
+        app.current_screen,
+        CurrentScreen::Editor
+            | CurrentScreen::MaterialEditor
+            | CurrentScreen::Render
+            | CurrentScreen::SkyEditor
+            | CurrentScreen::ArrayEditor
+            | CurrentScreen::Rename
+            | CurrentScreen::BulkEditor
+            | CurrentScreen::GroupEditor
+    )
+}
+
+/// Runs `edit` on the focused text field, keeping the height in line with a locked width.
+/// Returns true if the field changed.
+fn edit_focused(app: &mut App, edit: impl FnOnce(&mut TextInput) -> bool) -> bool {
+    if !on_editor(app) || app.picker.is_some() {
+        return false;
+    }
+    let edited = app.focused_input().is_some_and(edit);
+    if edited && app.is_editing(CurrentlyEditing::Width) {
+        app.sync_locked_height();
+    }
+    edited
+}
+
+/// Handles the keys every form shares: typing into text fields, stepping choices with Up/Down and
+/// moving between fields. Returns true if the key was used.
+fn handle_form_key(app: &mut App, key: KeyEvent) -> bool {
+    if !on_editor(app) {
+        return false;
+    }
+    if app.keys.action(Section::Editor, &key) == Some(Action::ColorPicker) && app.open_picker() {
+        return true;
+    }
+    if edit_focused(app, |input| input.handle_key(key)) {
+        return true;
+    }
+    match key.code {
+        KeyCode::Up | KeyCode::Down => app.cycle_choice(key.code == KeyCode::Up),
+        KeyCode::Tab | KeyCode::Right => {
+            app.change_editing(true);
+            true
+        }
+        KeyCode::BackTab | KeyCode::Left => {
+            app.change_editing(false);
+            true
+        }
+        _ => false,
+    }
+}
+
+/// Keys go to the color picker while it's open. Enter uses the picked color and Esc throws it
+/// away. Returns true if the key was used.
+fn handle_picker_key(app: &mut App, key: KeyEvent) -> bool {
+    match key.code {
+        KeyCode::Enter => app.apply_picker(),
+        KeyCode::Esc => app.picker = None,
+        _ => {
+            return app
+                .picker
+                .as_mut()
+                .is_some_and(|picker| picker.handle_key(key, &app.recent_colors))
+        }
+    }
+    true
+}
+
 fn run_app<B: Backend>(terminal: &mut Terminal<B>, app: &mut App) -> Result<bool> {
-    terminal.draw(|f| ui(f, app))?; //inital ui draw
+    draw(terminal, app)?; //inital ui draw
     loop {
-        if let Event::Key(key) = event::read()? {
+        // wait for input, picking up background previews as they finish
+        if !event::poll(Duration::from_millis(50))? {
+            let finished = app.preview_worker.borrow_mut().poll();
+            if let Some(preview) = finished {
+                *app.preview_cache.borrow_mut() = Some(preview);
+                draw(terminal, app)?;
+            }
+            continue;
+        }
+        let event = event::read()?;
+        if let Event::Mouse(mouse) = event {
+            if handle_mouse(app, mouse) {
+                draw(terminal, app)?;
+            }
+        } else if let Event::Key(key) = event {
             if key.kind == event::KeyEventKind::Release {
                 continue; //skips loggint the release of keys
             }
+            if app.picker.is_some() {
+                if handle_picker_key(app, key) {
+                    draw(terminal, app)?;
+                }
+                continue;
+            }
+            if handle_form_key(app, key) {
+                draw(terminal, app)?;
+                continue;
+            }
             match app.current_screen {
-                CurrentScreen::Main => match key.code {
-                    KeyCode::Esc => {
-                        app.current_screen = CurrentScreen::Confirmation;
-                    }
-                    KeyCode::Char('n') => {
+                CurrentScreen::Main => match app.keys.action(Section::Main, &key) {
+                    Some(Action::NewObject) => {
                         app.current_screen = CurrentScreen::Editor;
                         app.current_edit = Some(CurrentlyEditing::Size)
                     }
-                    KeyCode::Char('p') => {
+                    Some(Action::Preview) => {
                         app.current_screen = CurrentScreen::Preview;
                     }
-                    KeyCode::Char('m') => {
+                    Some(Action::NewMaterial) => {
                         app.current_screen = CurrentScreen::MaterialEditor;
                         app.current_edit = Some(CurrentlyEditing::MatType);
                         app.mat_type_input = Some(MaterialType::Lambertian);
                     }
-                    KeyCode::Char('q') => {
+                    Some(Action::Quit) => {
                         app.current_screen = CurrentScreen::Confirmation;
                     }
-                    KeyCode::Char('r') => {
+                    Some(Action::Render) => {
                         app.current_screen = CurrentScreen::Render;
-                        app.current_edit = Some(CurrentlyEditing::Width);
+                        app.current_edit = Some(CurrentlyEditing::Preset);
                     }
-                    KeyCode::Char('b') => {
+                    Some(Action::EditSky) => {
                         app.current_screen = CurrentScreen::SkyEditor;
                         app.current_edit = Some(CurrentlyEditing::SkyType)
                     }
-                    KeyCode::Char('d') => {
-                        if let Some(selected) = app.selected_object {
-                            app.world.objects.remove(selected);
-                            if selected > app.world.objects.len() {
-                                app.selected_object = Some(app.world.objects.len());
-                            }
-                            if selected == 0 {
-                                app.selected_object = None;
-                            }
-                        }
+                    Some(Action::FocusSelected) => {
+                        app.auto_focus();
                     }
-                    KeyCode::Up => {
-                        app.selected_object = if let Some(selected) = app.selected_object {
-                            if selected > 0 {
-                                Some(selected - 1)
-                            } else {
-                                Some(app.world.objects.len().saturating_sub(1))
-                            }
-                        } else {
-                            Some(app.world.objects.len().saturating_sub(1))
-                        }
+                    Some(Action::OpenViewer) => {
+                        app.open_viewer();
                     }
-                    KeyCode::Down => {
-                        app.selected_object = if let Some(selected) = app.selected_object {
-                            if selected < app.world.objects.len().saturating_sub(1) {
-                                Some(selected + 1)
-                            } else {
-                                Some(0)
-                            }
-                        } else {
-                            Some(0)
-                        }
+                    Some(Action::ToggleMap) => {
+                        app.show_map = !app.show_map;
+                    }
+                    Some(Action::ToggleSplit) => {
+                        app.split_preview = !app.split_preview;
+                    }
+                    Some(Action::ToggleMarked) => {
+                        app.toggle_marked();
+                    }
+                    Some(Action::ExtendUp) => {
+                        app.extend_selection(false);
+                    }
+                    Some(Action::ExtendDown) => {
+                        app.extend_selection(true);
+                    }
+                    Some(Action::MarkAll) => {
+                        app.mark_all();
+                    }
+                    // on a group, with nothing marked, it's the group's own transform that's edited
+                    Some(Action::BulkEdit) if !app.start_group_edit() => {
+                        app.start_bulk_edit();
+                    }
+                    Some(Action::DeleteObject) => {
+                        app.delete_selected();
+                    }
+                    Some(Action::DuplicateObject) => {
+                        app.duplicate_selected();
+                    }
+                    Some(Action::ArrayObject) if app.selected_object.is_some() => {
+                        app.current_screen = CurrentScreen::ArrayEditor;
+                        app.current_edit = Some(CurrentlyEditing::ArrayPattern);
                     }
+                    Some(Action::RenameObject) => {
+                        app.start_rename();
+                    }
+                    Some(Action::ToggleHidden) => {
+                        app.toggle_hidden();
+                    }
+                    Some(Action::SoloObject) => {
+                        app.toggle_solo();
+                    }
+                    Some(Action::GroupObjects) => {
+                        app.group_selected();
+                    }
+                    Some(Action::Ungroup) => {
+                        app.ungroup();
+                    }
+                    Some(Action::CollapseGroup) => {
+                        app.set_collapsed(true);
+                    }
+                    Some(Action::ExpandGroup) => {
+                        app.set_collapsed(false);
+                    }
+                    Some(Action::Undo) => {
+                        app.undo();
+                    }
+                    Some(Action::Redo) => {
+                        app.redo();
+                    }
+                    Some(Action::SelectPrevious) => app.move_cursor(false),
+                    Some(Action::SelectNext) => app.move_cursor(true),
                     _ => {}
                 },
                 CurrentScreen::Confirmation => match key.code {
@@ -137,161 +368,62 @@ fn run_app<B: Backend>(terminal: &mut Terminal<B>, app: &mut App) -> Result<bool
                         app.current_screen = CurrentScreen::Main;
                         app.current_edit = None;
                     }
-                    KeyCode::Tab => app.change_editing(true),
-                    KeyCode::BackTab => app.change_editing(false),
-                    KeyCode::Left => app.change_editing(false),
-                    KeyCode::Right => app.change_editing(true),
                     KeyCode::Enter => match app.save_object() {
                         Ok(_) => app.current_screen = CurrentScreen::Main,
                         Err(_) => {}
                     },
-                    KeyCode::Up => {
-                        if let Some(CurrentlyEditing::Material) = &app.current_edit {
-                            if app.material_input < app.materials.len() - 1 {
-                                app.material_input += 1;
-                            } else {
-                                app.material_input = 0;
-                            }
-                        }
-                        if let Some(CurrentlyEditing::Type) = &app.current_edit {
-                            if app.type_input < 1 {
-                                app.type_input += 1;
-                            } else {
-                                app.type_input = 0;
-                            }
-                        }
+                    _ => {}
+                },
+                CurrentScreen::ArrayEditor => match key.code {
+                    KeyCode::Esc => {
+                        app.current_screen = CurrentScreen::Main;
+                        app.current_edit = None;
                     }
-
-                    KeyCode::Down => {
-                        if let Some(CurrentlyEditing::Material) = &app.current_edit {
-                            if app.material_input > 0 {
-                                app.material_input -= 1;
-                            } else {
-                                app.material_input = app.materials.len() - 1
-                            }
-                        }
-                        if let Some(CurrentlyEditing::Type) = &app.current_edit {
-                            if app.type_input > 0 {
-                                app.type_input -= 1;
-                            } else {
-                                app.type_input = 1;
-                            }
-                        }
+                    // settings that don't parse leave the editor open to fix them
+                    KeyCode::Enter if app.array_selected().is_ok() => {
+                        app.current_screen = CurrentScreen::Main;
+                        app.current_edit = None;
                     }
-
-                    KeyCode::Char(value) => {
-                        if let Some(editing) = &app.current_edit {
-                            match editing {
-                                CurrentlyEditing::Size => {
-                                    app.size_input.push(value);
-                                }
-                                CurrentlyEditing::PositionX => {
-                                    app.position_input_x.push(value);
-                                }
-                                CurrentlyEditing::PositionY => {
-                                    app.position_input_y.push(value);
-                                }
-                                CurrentlyEditing::PositionZ => {
-                                    app.position_input_z.push(value);
-                                }
-                                _ => {}
-                            }
-                        }
+                    _ => {}
+                },
+                CurrentScreen::GroupEditor => match key.code {
+                    KeyCode::Esc => {
+                        app.current_screen = CurrentScreen::Main;
+                        app.current_edit = None;
                     }
-                    KeyCode::Backspace => {
-                        if let Some(editing) = &app.current_edit {
-                            match editing {
-                                CurrentlyEditing::Size => {
-                                    app.size_input.pop();
-                                }
-                                CurrentlyEditing::PositionX => {
-                                    app.position_input_x.pop();
-                                }
-                                CurrentlyEditing::PositionY => {
-                                    app.position_input_y.pop();
-                                }
-                                CurrentlyEditing::PositionZ => {
-                                    app.position_input_z.pop();
-                                }
-                                _ => {}
-                            }
-                        }
+                    // settings that don't parse leave the editor open to fix them
+                    KeyCode::Enter if app.save_group().is_ok() => {
+                        app.current_screen = CurrentScreen::Main;
+                        app.current_edit = None;
                     }
-
                     _ => {}
                 },
-                CurrentScreen::MaterialEditor => match key.code {
-                    KeyCode::Tab => app.change_editing(true),
-                    KeyCode::BackTab => app.change_editing(false),
-                    KeyCode::Left => app.change_editing(false),
-                    KeyCode::Right => app.change_editing(true),
-                    KeyCode::Esc => app.current_screen = CurrentScreen::Main,
-                    KeyCode::Char(value) => {
-                        if let Some(editing) = &app.current_edit {
-                            match editing {
-                                CurrentlyEditing::MatColor => {
-                                    app.mat_color_input.push(value);
-                                }
-                                CurrentlyEditing::MatProperty => {
-                                    app.mat_other_input.push(value);
-                                }
-                                CurrentlyEditing::MatName => {
-                                    app.mat_name_input.push(value);
-                                }
-                                _ => {}
-                            }
-                        }
+                CurrentScreen::BulkEditor => match key.code {
+                    KeyCode::Esc => {
+                        app.current_screen = CurrentScreen::Main;
+                        app.current_edit = None;
                     }
-                    KeyCode::Backspace => {
-                        if let Some(editing) = &app.current_edit {
-                            match editing {
-                                CurrentlyEditing::MatColor => {
-                                    app.mat_color_input.pop();
-                                }
-                                CurrentlyEditing::MatProperty => {
-                                    app.mat_other_input.pop();
-                                }
-                                CurrentlyEditing::MatName => {
-                                    app.mat_name_input.pop();
-                                }
-                                _ => {}
-                            }
-                        }
+                    // settings that don't parse leave the editor open to fix them
+                    KeyCode::Enter if app.bulk_edit().is_ok() => {
+                        app.current_screen = CurrentScreen::Main;
+                        app.current_edit = None;
                     }
-                    KeyCode::Up => {
-                        if let Some(editing) = &app.current_edit {
-                            match editing {
-                                CurrentlyEditing::MatType => {
-                                    if let Some(mat_type) = &app.mat_type_input {
-                                        app.mat_type_input = match mat_type {
-                                            MaterialType::Lambertian => Some(MaterialType::Metal),
-                                            MaterialType::Metal => Some(MaterialType::Dielectric),
-                                            MaterialType::Dielectric => Some(MaterialType::Normal),
-                                            MaterialType::Normal => Some(MaterialType::Lambertian),
-                                        }
-                                    }
-                                }
-                                _ => {}
-                            }
-                        }
+                    _ => {}
+                },
+                CurrentScreen::Rename => match key.code {
+                    KeyCode::Esc => {
+                        app.current_screen = CurrentScreen::Main;
+                        app.current_edit = None;
                     }
-                    KeyCode::Down => {
-                        if let Some(editing) = &app.current_edit {
-                            match editing {
-                                CurrentlyEditing::MatType => {
-                                    if let Some(mat_type) = &app.mat_type_input {
-                                        app.mat_type_input = match mat_type {
-                                            MaterialType::Lambertian => Some(MaterialType::Normal),
-                                            MaterialType::Metal => Some(MaterialType::Lambertian),
-                                            MaterialType::Dielectric => Some(MaterialType::Metal),
-                                            MaterialType::Normal => Some(MaterialType::Dielectric),
-                                        }
-                                    }
-                                }
-                                _ => {}
-                            }
-                        }
+                    KeyCode::Enter => {
+                        app.rename_selected();
+                        app.current_screen = CurrentScreen::Main;
+                        app.current_edit = None;
                     }
+                    _ => {}
+                },
+                CurrentScreen::MaterialEditor => match key.code {
+                    KeyCode::Esc => app.current_screen = CurrentScreen::Main,
                     KeyCode::Enter => match app.save_material() {
                         Ok(_) => {
                             app.current_screen = CurrentScreen::Main;
@@ -307,189 +439,109 @@ fn run_app<B: Backend>(terminal: &mut Terminal<B>, app: &mut App) -> Result<bool
                     }
                     KeyCode::Enter => match render_image(app, terminal) {
                         Ok(_) => {
-                            app.current_screen = CurrentScreen::Main;
+                            if !app.open_viewer() {
+                                app.current_screen = CurrentScreen::Main;
+                            }
                         }
                         Err(_) => app.current_screen = CurrentScreen::Render,
                     },
-                    KeyCode::Tab => app.change_editing(true),
-                    KeyCode::BackTab => app.change_editing(false),
-                    KeyCode::Left => app.change_editing(false),
-                    KeyCode::Right => app.change_editing(true),
-                    KeyCode::Char(value) => {
-                        if let Some(editing) = &app.current_edit {
-                            match editing {
-                                CurrentlyEditing::Width => {
-                                    app.image_width.push(value);
-                                }
-                                CurrentlyEditing::Height => {
-                                    app.image_height.push(value);
-                                }
-                                CurrentlyEditing::ImgName => {
-                                    app.image_name_input.push(value);
-                                }
-                                CurrentlyEditing::Samples => {
-                                    app.samples.push(value);
-                                }
-                                CurrentlyEditing::Bounces => {
-                                    app.bounces.push(value);
-                                }
-                                CurrentlyEditing::CamX => {
-                                    app.camx.push(value);
-                                }
-                                CurrentlyEditing::CamY => {
-                                    app.camy.push(value);
-                                }
-                                CurrentlyEditing::CamZ => {
-                                    app.camz.push(value);
-                                }
-                                CurrentlyEditing::LookX => {
-                                    app.lookx.push(value);
-                                }
-                                CurrentlyEditing::LookY => {
-                                    app.looky.push(value);
-                                }
-                                CurrentlyEditing::LookZ => {
-                                    app.lookz.push(value);
-                                }
-                                CurrentlyEditing::Fov => {
-                                    app.fov.push(value);
-                                }
-                                CurrentlyEditing::FocusDist => {
-                                    app.focus_dist.push(value);
-                                }
-                                CurrentlyEditing::Aperture => {
-                                    app.aperture.push(value);
-                                }
-                                _ => {}
-                            }
-                        }
-                    }
-                    KeyCode::Backspace => {
-                        if let Some(editing) = &app.current_edit {
-                            match editing {
-                                CurrentlyEditing::Width => {
-                                    app.image_width.pop();
-                                }
-                                CurrentlyEditing::Height => {
-                                    app.image_height.pop();
-                                }
-                                CurrentlyEditing::ImgName => {
-                                    app.image_name_input.pop();
-                                }
-                                CurrentlyEditing::Samples => {
-                                    app.samples.pop();
-                                }
-                                CurrentlyEditing::Bounces => {
-                                    app.bounces.pop();
-                                }
-                                CurrentlyEditing::CamX => {
-                                    app.camx.pop();
-                                }
-                                CurrentlyEditing::CamY => {
-                                    app.camy.pop();
-                                }
-                                CurrentlyEditing::CamZ => {
-                                    app.camz.pop();
-                                }
-                                CurrentlyEditing::LookX => {
-                                    app.lookx.pop();
-                                }
-                                CurrentlyEditing::LookY => {
-                                    app.looky.pop();
-                                }
-                                CurrentlyEditing::LookZ => {
-                                    app.lookz.pop();
-                                }
-                                CurrentlyEditing::Fov => {
-                                    app.fov.pop();
-                                }
-                                CurrentlyEditing::FocusDist => {
-                                    app.focus_dist.pop();
-                                }
-                                CurrentlyEditing::Aperture => {
-                                    app.aperture.pop();
-                                }
-                                _ => {}
-                            }
-                        }
+                    _ if app.keys.action(Section::Render, &key) == Some(Action::AutoFocus) => {
+                        app.auto_focus();
                     }
                     _ => {}
                 },
-                CurrentScreen::Preview => match key.code {
-                    KeyCode::Esc => {
+                CurrentScreen::Preview => match app.keys.action(Section::Preview, &key) {
+                    _ if key.code == KeyCode::Esc => {
                         app.current_screen = CurrentScreen::Main;
-                        terminal.draw(|f| ui(f, app))?; //only draws if exiting or updating to avoid lag.
+                        draw(terminal, app)?; //only draws if exiting or updating to avoid lag.
                     }
-                    KeyCode::Char('f') => {
+                    Some(Action::PreviewFullScreen) => {
                         app.current_screen = CurrentScreen::PreviewFull;
-                        terminal.draw(|f| ui(f, app))?;
+                        draw(terminal, app)?;
+                    }
+                    Some(Action::GraphicsMode) => {
+                        app.preview_mode = app.preview_mode.next();
+                        draw(terminal, app)?;
+                    }
+                    Some(Action::Shading) => {
+                        app.shading = app.shading.next();
+                        draw(terminal, app)?;
+                    }
+                    Some(Action::MoreSamples) => {
+                        app.preview_quality.step_samples(true);
+                        draw(terminal, app)?;
+                    }
+                    Some(Action::FewerSamples) => {
+                        app.preview_quality.step_samples(false);
+                        draw(terminal, app)?;
+                    }
+                    Some(Action::MoreBounces) => {
+                        app.preview_quality.step_bounces(true);
+                        draw(terminal, app)?;
+                    }
+                    Some(Action::FewerBounces) => {
+                        app.preview_quality.step_bounces(false);
+                        draw(terminal, app)?;
+                    }
+                    Some(Action::LowerResolution) => {
+                        app.preview_quality.step_divisor(true);
+                        draw(terminal, app)?;
+                    }
+                    Some(Action::HigherResolution) => {
+                        app.preview_quality.step_divisor(false);
+                        draw(terminal, app)?;
                     }
                     _ => continue,
                 },
                 CurrentScreen::PreviewFull => match key.code {
                     KeyCode::Esc => {
                         app.current_screen = CurrentScreen::Preview;
-                        terminal.draw(|f| ui(f, app))?;
+                        draw(terminal, app)?;
                     }
                     _ => continue,
                 },
+                CurrentScreen::Viewer => {
+                    if let Some(viewer) = &mut app.viewer {
+                        match app.keys.action(Section::Viewer, &key) {
+                            _ if key.code == KeyCode::Esc => {
+                                app.current_screen = CurrentScreen::Main;
+                            }
+                            Some(Action::ZoomIn) => viewer.zoom_by(1.25),
+                            Some(Action::ZoomOut) => viewer.zoom_by(0.8),
+                            Some(Action::ResetView) => viewer.reset(),
+                            Some(Action::ViewerFullScreen) => {
+                                viewer.fullscreen = !viewer.fullscreen;
+                            }
+                            Some(Action::PanUp) => viewer.pan(0.0, -0.1),
+                            Some(Action::PanDown) => viewer.pan(0.0, 0.1),
+                            Some(Action::PanLeft) => viewer.pan(-0.1, 0.0),
+                            Some(Action::PanRight) => viewer.pan(0.1, 0.0),
+                            _ => continue,
+                        }
+                    } else {
+                        app.current_screen = CurrentScreen::Main;
+                    }
+                }
                 CurrentScreen::SkyEditor => match key.code {
                     KeyCode::Esc => {
                         app.current_screen = CurrentScreen::Main;
                     }
-                    KeyCode::Tab => app.change_editing(true),
-                    KeyCode::BackTab => app.change_editing(false),
-                    KeyCode::Left => app.change_editing(false),
-                    KeyCode::Right => app.change_editing(true),
                     KeyCode::Enter => match app.save_sky() {
                         Ok(_) => app.current_screen = CurrentScreen::Main,
                         Err(_) => {}
                     },
-                    KeyCode::Char(value) => {
-                        if let Some(editing) = &app.current_edit {
-                            match editing {
-                                CurrentlyEditing::SkyColor1 => {
-                                    app.sky_color1.push(value);
-                                }
-                                CurrentlyEditing::SkyColor2 => {
-                                    app.sky_color2.push(value);
-                                }
-                                _ => {}
-                            }
-                        }
-                    }
-                    KeyCode::Backspace => {
-                        if let Some(editing) = &app.current_edit {
-                            match editing {
-                                CurrentlyEditing::SkyColor1 => {
-                                    app.sky_color1.pop();
-                                }
-                                CurrentlyEditing::SkyColor2 => {
-                                    app.sky_color2.pop();
-                                }
-                                _ => {}
-                            }
-                        }
-                    }
-                    KeyCode::Up => {
-                        if let Some(editing) = &app.current_edit {
-                            match editing {
-                                CurrentlyEditing::SkyType => {
-                                    app.sky_type = match app.sky_type {
-                                        SkyType::Gradient => SkyType::Solid,
-                                        SkyType::Solid => SkyType::Gradient,
-                                    }
-                                }
-                                _ => {}
-                            }
-                        }
-                    }
                     _ => continue,
                 },
             }
-            terminal.draw(|f| ui(f, app))?; //redraw ui for key events
-        } else if let Event::Resize(_, _) = event::read()? {
-            terminal.draw(|f| ui(f, app))?;
+            draw(terminal, app)?; //redraw ui for key events
+        } else if let Event::Paste(text) = event {
+            // pasted on a single line
+            let text: String = text.chars().filter(|c| !c.is_control()).collect();
+            if edit_focused(app, |input| input.insert(text.trim())) {
+                draw(terminal, app)?;
+            }
+        } else if let Event::Resize(_, _) = event {
+            draw(terminal, app)?;
         }
     }
 }
@@ -0,0 +1,221 @@
+#![warn(clippy::pedantic)]
+//! A top-down map of the scene, drawn straight from the inputs without any raytracing. The map
+//! looks down the Y axis at the XZ plane, with -Z pointing up the screen.
+
+use ratatui::{
+    layout::Rect,
+    style::{Color, Style},
+    symbols::Marker,
+    widgets::{
+        canvas::{Canvas, Circle, Context, Line},
+        Block, BorderType, Borders,
+    },
+    Frame,
+};
+use rtwlib::vec3::{Point3, Vec3};
+
+use crate::{
+    app::{shown, CurrentScreen, ObjectType, SceneObject},
+    projection::Projection,
+    App,
+};
+
+/// Space left around the scene, in world units
+const MARGIN: f64 = 1.0;
+
+/// Converts a point in the world to map coordinates.
+fn to_map(point: Point3) -> (f64, f64) {
+    (point.x, -point.z)
+}
+
+pub fn render_map(frame: &mut Frame, area: Rect, app: &App) {
+    let block = Block::default()
+        .title("Scene Map (top down)")
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded);
+    let inner = block.inner(area);
+    if inner.is_empty() {
+        frame.render_widget(block, area);
+        return;
+    }
+
+    let camera = app.camera().ok();
+    // an object that's still being typed into the editor shows up as well
+    let pending = match app.current_screen {
+        CurrentScreen::Editor => app.pending_object(),
+        _ => None,
+    };
+
+    let objects = app.placed_objects();
+    // everything that should fit on the map
+    let mut points: Vec<(f64, f64, f64)> = objects
+        .iter()
+        .chain(&pending)
+        .filter(|object| object.kind == ObjectType::Sphere)
+        .map(|object| {
+            let (x, y) = to_map(object.position);
+            (x, y, object.size)
+        })
+        .collect();
+    if let Some(cam) = &camera {
+        let (x, y) = to_map(cam.lookfrom);
+        points.push((x, y, 0.0));
+        let (x, y) = to_map(cam.lookat);
+        points.push((x, y, 0.0));
+    }
+    let (x_bounds, y_bounds) = bounds(&points, inner);
+
+    let canvas = Canvas::default()
+        .block(block)
+        .marker(Marker::Braille)
+        .x_bounds(x_bounds)
+        .y_bounds(y_bounds)
+        .paint(|ctx| {
+            let shown = shown(&app.objects);
+            let targets = app.targets();
+            for (i, object) in objects.iter().enumerate() {
+                // objects left out of the render are grayed out, but stay on the map
+                let color = if Some(i) == app.selected_object || targets.contains(&i) {
+                    app.theme.highlight
+                } else if shown.contains(&i) {
+                    app.theme.text
+                } else {
+                    app.theme.muted
+                };
+                draw_object(ctx, object, color, x_bounds, y_bounds);
+            }
+            if let Some(object) = &pending {
+                draw_object(ctx, object, app.theme.muted, x_bounds, y_bounds);
+            }
+            if let Some(cam) = &camera {
+                draw_camera(ctx, app, cam.lookfrom, cam.lookat, cam.vfov);
+            }
+        });
+    frame.render_widget(canvas, area);
+}
+
+/// Map bounds that fit all of `points` (x, y and a radius), stretched so that a world unit is as
+/// tall as it is wide on screen. Terminal cells are about twice as tall as they are wide.
+fn bounds(points: &[(f64, f64, f64)], area: Rect) -> ([f64; 2], [f64; 2]) {
+    let (mut left, mut right, mut bottom, mut top) = (-MARGIN, MARGIN, -MARGIN, MARGIN);
+    for &(x, y, radius) in points {
+        left = left.min(x - radius - MARGIN);
+        right = right.max(x + radius + MARGIN);
+        bottom = bottom.min(y - radius - MARGIN);
+        top = top.max(y + radius + MARGIN);
+    }
+
+    let aspect = f64::from(area.width) / (f64::from(area.height) * 2.0);
+    let (width, height) = (right - left, top - bottom);
+    if width / height < aspect {
+        let grow = (height * aspect - width) / 2.0;
+        (left, right) = (left - grow, right + grow);
+    } else {
+        let grow = (width / aspect - height) / 2.0;
+        (bottom, top) = (bottom - grow, top + grow);
+    }
+    ([left, right], [bottom, top])
+}
+
+fn draw_object(
+    ctx: &mut Context,
+    object: &SceneObject,
+    color: Color,
+    x_bounds: [f64; 2],
+    y_bounds: [f64; 2],
+) {
+    match object.kind {
+        ObjectType::Sphere => {
+            let (cx, cy) = to_map(object.position);
+            ctx.draw(&Circle {
+                x: cx,
+                y: cy,
+                radius: object.size,
+                color,
+            });
+        }
+        ObjectType::Plane => {
+            // drawn where the plane crosses y = 0, the line nx * x + ny * y = offset on the map.
+            // Flat planes cover the whole map, so there's nothing useful to draw for them.
+            let normal = object.position;
+            let (nx, ny) = (normal.x, -normal.z);
+            let offset = normal.y * object.size;
+            if nx.abs() < 1e-6 && ny.abs() < 1e-6 {
+                return;
+            }
+            let (x1, y1, x2, y2) = if ny.abs() > nx.abs() {
+                let [left, right] = x_bounds;
+                (left, (offset - nx * left) / ny, right, (offset - nx * right) / ny)
+            } else {
+                let [bottom, top] = y_bounds;
+                ((offset - ny * bottom) / nx, bottom, (offset - ny * top) / nx, top)
+            };
+            ctx.draw(&Line {
+                x1,
+                y1,
+                x2,
+                y2,
+                color,
+            });
+        }
+    }
+}
+
+/// Draws the camera as a dot, a line to where it's looking, and the edges of what it can see.
+fn draw_camera(ctx: &mut Context, app: &App, lookfrom: Point3, lookat: Point3, vfov: f64) {
+    let (x, y) = to_map(lookfrom);
+    let (tx, ty) = to_map(lookat);
+    ctx.draw(&Line {
+        x1: x,
+        y1: y,
+        x2: tx,
+        y2: ty,
+        color: app.theme.camera,
+    });
+
+    let forward = Vec3::new(tx - x, ty - y, 0.0);
+    let length = forward.length();
+    // looking straight up or down, there's no frustum to see from above
+    if length > 1e-6 {
+        let (dx, dy) = (forward.x / length, forward.y / length);
+        let aspect = app.render_size().map_or(16.0 / 9.0, |(width, height)| {
+            f64::from(width) / f64::from(height.max(1))
+        });
+        let reach = length.max(1.0) * 1.5;
+        match app.projection {
+            Projection::Perspective => {
+                let half = ((vfov.to_radians() / 2.0).tan() * aspect).atan();
+                for angle in [-half, half] {
+                    let (sin, cos) = angle.sin_cos();
+                    ctx.draw(&Line {
+                        x1: x,
+                        y1: y,
+                        x2: x + (dx * cos - dy * sin) * reach,
+                        y2: y + (dx * sin + dy * cos) * reach,
+                        color: app.theme.camera_view,
+                    });
+                }
+            }
+            Projection::Orthographic => {
+                let half = app.ortho_width.parse::<f64>().unwrap_or(0.0) / 2.0;
+                for side in [-half, half] {
+                    let (ox, oy) = (x - dy * side, y + dx * side);
+                    ctx.draw(&Line {
+                        x1: ox,
+                        y1: oy,
+                        x2: ox + dx * reach,
+                        y2: oy + dy * reach,
+                        color: app.theme.camera_view,
+                    });
+                }
+            }
+        }
+    }
+
+    ctx.layer();
+    ctx.print(
+        x,
+        y,
+        ratatui::text::Line::styled("●", Style::default().fg(app.theme.camera)),
+    );
+}
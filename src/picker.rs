@@ -0,0 +1,563 @@
+#![warn(clippy::pedantic)]
+//! A popup for picking the value of a color field, with HSV or RGB sliders, the colors picked
+//! recently and the named CSS colors.
+
+use std::cell::{Cell, RefCell};
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::{
+    layout::{Alignment, Constraint, Direction, Layout, Position, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, BorderType, Borders, Clear, Paragraph},
+    Frame,
+};
+
+use crate::{app::CurrentlyEditing, centered_rect, App};
+
+/// How many picked colors are remembered.
+pub const RECENT_COLORS: usize = 12;
+
+/// Width of a palette swatch, plus one column between swatches.
+const SWATCH_WIDTH: u16 = 3;
+
+/// The CSS named colors, grouped by hue the way the spec lists them, without the "grey" spellings.
+#[rustfmt::skip]
+const NAMED_COLORS: &[(&str, [u8; 3])] = &[
+    ("indianred", [205, 92, 92]), ("lightcoral", [240, 128, 128]), ("salmon", [250, 128, 114]),
+    ("darksalmon", [233, 150, 122]), ("lightsalmon", [255, 160, 122]), ("crimson", [220, 20, 60]),
+    ("red", [255, 0, 0]), ("firebrick", [178, 34, 34]), ("darkred", [139, 0, 0]),
+    ("pink", [255, 192, 203]), ("lightpink", [255, 182, 193]), ("hotpink", [255, 105, 180]),
+    ("deeppink", [255, 20, 147]), ("mediumvioletred", [199, 21, 133]),
+    ("palevioletred", [219, 112, 147]),
+    ("coral", [255, 127, 80]), ("tomato", [255, 99, 71]), ("orangered", [255, 69, 0]),
+    ("darkorange", [255, 140, 0]), ("orange", [255, 165, 0]),
+    ("gold", [255, 215, 0]), ("yellow", [255, 255, 0]), ("lightyellow", [255, 255, 224]),
+    ("lemonchiffon", [255, 250, 205]), ("lightgoldenrodyellow", [250, 250, 210]),
+    ("papayawhip", [255, 239, 213]), ("moccasin", [255, 228, 181]), ("peachpuff", [255, 218, 185]),
+    ("palegoldenrod", [238, 232, 170]), ("khaki", [240, 230, 140]), ("darkkhaki", [189, 183, 107]),
+    ("lavender", [230, 230, 250]), ("thistle", [216, 191, 216]), ("plum", [221, 160, 221]),
+    ("violet", [238, 130, 238]), ("orchid", [218, 112, 214]), ("fuchsia", [255, 0, 255]),
+    ("mediumorchid", [186, 85, 211]), ("mediumpurple", [147, 112, 219]),
+    ("rebeccapurple", [102, 51, 153]), ("blueviolet", [138, 43, 226]),
+    ("darkviolet", [148, 0, 211]), ("darkorchid", [153, 50, 204]), ("darkmagenta", [139, 0, 139]),
+    ("purple", [128, 0, 128]), ("indigo", [75, 0, 130]), ("slateblue", [106, 90, 205]),
+    ("darkslateblue", [72, 61, 139]), ("mediumslateblue", [123, 104, 238]),
+    ("greenyellow", [173, 255, 47]), ("chartreuse", [127, 255, 0]), ("lawngreen", [124, 252, 0]),
+    ("lime", [0, 255, 0]), ("limegreen", [50, 205, 50]), ("palegreen", [152, 251, 152]),
+    ("lightgreen", [144, 238, 144]), ("mediumspringgreen", [0, 250, 154]),
+    ("springgreen", [0, 255, 127]), ("mediumseagreen", [60, 179, 113]),
+    ("seagreen", [46, 139, 87]), ("forestgreen", [34, 139, 34]), ("green", [0, 128, 0]),
+    ("darkgreen", [0, 100, 0]), ("yellowgreen", [154, 205, 50]), ("olivedrab", [107, 142, 35]),
+    ("olive", [128, 128, 0]), ("darkolivegreen", [85, 107, 47]),
+    ("mediumaquamarine", [102, 205, 170]), ("darkseagreen", [143, 188, 139]),
+    ("lightseagreen", [32, 178, 170]), ("darkcyan", [0, 139, 139]), ("teal", [0, 128, 128]),
+    ("aqua", [0, 255, 255]), ("lightcyan", [224, 255, 255]), ("paleturquoise", [175, 238, 238]),
+    ("aquamarine", [127, 255, 212]), ("turquoise", [64, 224, 208]),
+    ("mediumturquoise", [72, 209, 204]), ("darkturquoise", [0, 206, 209]),
+    ("cadetblue", [95, 158, 160]), ("steelblue", [70, 130, 180]),
+    ("lightsteelblue", [176, 196, 222]), ("powderblue", [176, 224, 230]),
+    ("lightblue", [173, 216, 230]), ("skyblue", [135, 206, 235]), ("lightskyblue", [135, 206, 250]),
+    ("deepskyblue", [0, 191, 255]), ("dodgerblue", [30, 144, 255]),
+    ("cornflowerblue", [100, 149, 237]), ("royalblue", [65, 105, 225]), ("blue", [0, 0, 255]),
+    ("mediumblue", [0, 0, 205]), ("darkblue", [0, 0, 139]), ("navy", [0, 0, 128]),
+    ("midnightblue", [25, 25, 112]),
+    ("cornsilk", [255, 248, 220]), ("blanchedalmond", [255, 235, 205]), ("bisque", [255, 228, 196]),
+    ("navajowhite", [255, 222, 173]), ("wheat", [245, 222, 179]), ("burlywood", [222, 184, 135]),
+    ("tan", [210, 180, 140]), ("rosybrown", [188, 143, 143]), ("sandybrown", [244, 164, 96]),
+    ("goldenrod", [218, 165, 32]), ("darkgoldenrod", [184, 134, 11]), ("peru", [205, 133, 63]),
+    ("chocolate", [210, 105, 30]), ("saddlebrown", [139, 69, 19]), ("sienna", [160, 82, 45]),
+    ("brown", [165, 42, 42]), ("maroon", [128, 0, 0]),
+    ("white", [255, 255, 255]), ("snow", [255, 250, 250]), ("honeydew", [240, 255, 240]),
+    ("mintcream", [245, 255, 250]), ("azure", [240, 255, 255]), ("aliceblue", [240, 248, 255]),
+    ("ghostwhite", [248, 248, 255]), ("whitesmoke", [245, 245, 245]), ("seashell", [255, 245, 238]),
+    ("beige", [245, 245, 220]), ("oldlace", [253, 245, 230]), ("floralwhite", [255, 250, 240]),
+    ("ivory", [255, 255, 240]), ("antiquewhite", [250, 235, 215]), ("linen", [250, 240, 230]),
+    ("lavenderblush", [255, 240, 245]), ("mistyrose", [255, 228, 225]),
+    ("gainsboro", [220, 220, 220]), ("lightgray", [211, 211, 211]), ("silver", [192, 192, 192]),
+    ("darkgray", [169, 169, 169]), ("gray", [128, 128, 128]), ("dimgray", [105, 105, 105]),
+    ("lightslategray", [119, 136, 153]), ("slategray", [112, 128, 144]),
+    ("darkslategray", [47, 79, 79]), ("black", [0, 0, 0]),
+];
+
+#[derive(Clone, Copy, PartialEq)]
+enum PickerMode {
+    Hsv,
+    Rgb,
+}
+
+/// The part of the picker the arrow keys work on.
+#[derive(Clone, Copy, PartialEq)]
+enum Focus {
+    Slider(usize),
+    Recent,
+    Named,
+}
+
+/// Something that can be clicked, stored with where it was last drawn.
+#[derive(Clone, Copy)]
+enum Target {
+    Slider(usize),
+    Recent(usize),
+    Named(usize),
+}
+
+pub struct ColorPicker {
+    /// The color field the picked color goes back to
+    pub field: CurrentlyEditing,
+    mode: PickerMode,
+    rgb: [u8; 3],
+    /// Hue in degrees, saturation and value from 0 to 1. Kept next to `rgb` so a grey doesn't
+    /// lose its hue while dragging the saturation down and back up.
+    hsv: [f64; 3],
+    focus: Focus,
+    recent: usize,
+    named: usize,
+    /// Named colors in each row of the grid, as last drawn
+    named_columns: Cell<usize>,
+    targets: RefCell<Vec<(Rect, Target)>>,
+}
+
+#[allow(
+    clippy::cast_possible_truncation,
+    clippy::cast_sign_loss,
+    clippy::many_single_char_names
+)]
+fn hsv_to_rgb([h, s, v]: [f64; 3]) -> [u8; 3] {
+    let sector = (h / 60.0).rem_euclid(6.0);
+    let chroma = v * s;
+    let x = chroma * (1.0 - (sector % 2.0 - 1.0).abs());
+    let (r, g, b) = match sector as u8 {
+        0 => (chroma, x, 0.0),
+        1 => (x, chroma, 0.0),
+        2 => (0.0, chroma, x),
+        3 => (0.0, x, chroma),
+        4 => (x, 0.0, chroma),
+        _ => (chroma, 0.0, x),
+    };
+    let byte = |c: f64| ((c + v - chroma) * 255.0).round().clamp(0.0, 255.0) as u8;
+    [byte(r), byte(g), byte(b)]
+}
+
+// max is one of the channels exactly, so comparing them finds which one it is
+#[allow(clippy::float_cmp)]
+fn rgb_to_hsv(rgb: [u8; 3]) -> [f64; 3] {
+    let [r, g, b] = rgb.map(|c| f64::from(c) / 255.0);
+    let max = r.max(g).max(b);
+    let chroma = max - r.min(g).min(b);
+    let hue = if chroma == 0.0 {
+        0.0
+    } else if max == r {
+        60.0 * ((g - b) / chroma).rem_euclid(6.0)
+    } else if max == g {
+        60.0 * ((b - r) / chroma + 2.0)
+    } else {
+        60.0 * ((r - g) / chroma + 4.0)
+    };
+    let saturation = if max == 0.0 { 0.0 } else { chroma / max };
+    [hue, saturation, max]
+}
+
+/// Black or white, whichever shows up better on `rgb`.
+fn contrast([r, g, b]: [u8; 3]) -> Color {
+    let luma = 0.299 * f64::from(r) + 0.587 * f64::from(g) + 0.114 * f64::from(b);
+    if luma > 140.0 {
+        Color::Black
+    } else {
+        Color::White
+    }
+}
+
+fn to_color([r, g, b]: [u8; 3]) -> Color {
+    Color::Rgb(r, g, b)
+}
+
+/// Moves `index` by `by` within `len` items, stopping at the ends.
+fn nudge(index: usize, by: isize, len: usize) -> usize {
+    index.saturating_add_signed(by).min(len.saturating_sub(1))
+}
+
+impl ColorPicker {
+    pub fn new(field: CurrentlyEditing, rgb: [u8; 3]) -> Self {
+        Self {
+            field,
+            mode: PickerMode::Hsv,
+            rgb,
+            hsv: rgb_to_hsv(rgb),
+            focus: Focus::Slider(0),
+            recent: 0,
+            named: NAMED_COLORS
+                .iter()
+                .position(|(_, color)| *color == rgb)
+                .unwrap_or(0),
+            named_columns: Cell::new(1),
+            targets: RefCell::new(Vec::new()),
+        }
+    }
+
+    /// The picked color as the hex the color fields take.
+    pub fn hex(&self) -> String {
+        let [r, g, b] = self.rgb;
+        format!("{r:02x}{g:02x}{b:02x}")
+    }
+
+    pub fn rgb(&self) -> [u8; 3] {
+        self.rgb
+    }
+
+    fn set_rgb(&mut self, rgb: [u8; 3]) {
+        let hsv = rgb_to_hsv(rgb);
+        // greys and black have no hue or saturation of their own, so keep the old ones
+        self.hsv = match (hsv[1] == 0.0, hsv[2] == 0.0) {
+            (_, true) => [self.hsv[0], self.hsv[1], 0.0],
+            (true, false) => [self.hsv[0], 0.0, hsv[2]],
+            (false, false) => hsv,
+        };
+        self.rgb = rgb;
+    }
+
+    /// The largest value of a slider, in the units it's shown in.
+    fn max(&self, slider: usize) -> f64 {
+        match (self.mode, slider) {
+            (PickerMode::Hsv, 0) => 360.0,
+            (PickerMode::Hsv, _) => 100.0,
+            (PickerMode::Rgb, _) => 255.0,
+        }
+    }
+
+    fn value(&self, slider: usize) -> f64 {
+        match self.mode {
+            PickerMode::Hsv if slider == 0 => self.hsv[0],
+            PickerMode::Hsv => self.hsv[slider] * 100.0,
+            PickerMode::Rgb => f64::from(self.rgb[slider]),
+        }
+    }
+
+    /// The color with one slider moved to `value`, leaving the picker as it is.
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    fn with_value(&self, slider: usize, value: f64) -> ([u8; 3], [f64; 3]) {
+        let value = value.clamp(0.0, self.max(slider));
+        match self.mode {
+            PickerMode::Hsv => {
+                let mut hsv = self.hsv;
+                hsv[slider] = if slider == 0 { value } else { value / 100.0 };
+                (hsv_to_rgb(hsv), hsv)
+            }
+            PickerMode::Rgb => {
+                let mut rgb = self.rgb;
+                rgb[slider] = value.round() as u8;
+                (rgb, rgb_to_hsv(rgb))
+            }
+        }
+    }
+
+    fn set_value(&mut self, slider: usize, value: f64) {
+        match self.mode {
+            PickerMode::Hsv => (self.rgb, self.hsv) = self.with_value(slider, value),
+            PickerMode::Rgb => self.set_rgb(self.with_value(slider, value).0),
+        }
+    }
+
+    /// Moves a slider by 10, or by 1 if `fine` is set.
+    fn step(&mut self, slider: usize, up: bool, fine: bool) {
+        let step = if fine { 1.0 } else { 10.0 };
+        let value = self.value(slider);
+        self.set_value(slider, if up { value + step } else { value - step });
+    }
+
+    fn pick_recent(&mut self, index: usize, recent: &[[u8; 3]]) {
+        if let Some(&rgb) = recent.get(index) {
+            self.recent = index;
+            self.focus = Focus::Recent;
+            self.set_rgb(rgb);
+        }
+    }
+
+    fn pick_named(&mut self, index: usize) {
+        if let Some(&(_, rgb)) = NAMED_COLORS.get(index) {
+            self.named = index;
+            self.focus = Focus::Named;
+            self.set_rgb(rgb);
+        }
+    }
+
+    /// Moves focus up or down: through the sliders, then the recent colors, then the rows of
+    /// named colors.
+    fn move_focus(&mut self, down: bool, recent: &[[u8; 3]]) {
+        let columns = self.named_columns.get().max(1);
+        self.focus = match (self.focus, down) {
+            (Focus::Slider(0), false) => Focus::Slider(0),
+            (Focus::Slider(slider), false) => Focus::Slider(slider - 1),
+            (Focus::Slider(2), true) if !recent.is_empty() => Focus::Recent,
+            (Focus::Slider(2) | Focus::Recent, true) => Focus::Named,
+            (Focus::Slider(slider), true) => Focus::Slider(slider + 1),
+            (Focus::Recent, false) => Focus::Slider(2),
+            (Focus::Named, false) if self.named < columns => {
+                if recent.is_empty() {
+                    Focus::Slider(2)
+                } else {
+                    Focus::Recent
+                }
+            }
+            (Focus::Named, false) => {
+                self.named -= columns;
+                Focus::Named
+            }
+            (Focus::Named, true) => {
+                if self.named + columns < NAMED_COLORS.len() {
+                    self.named += columns;
+                }
+                Focus::Named
+            }
+        };
+        match self.focus {
+            Focus::Recent => self.pick_recent(self.recent.min(recent.len() - 1), recent),
+            Focus::Named => self.pick_named(self.named),
+            Focus::Slider(_) => {}
+        }
+    }
+
+    /// Moves the focused slider, or along the focused palette.
+    fn nudge(&mut self, up: bool, fine: bool, recent: &[[u8; 3]]) {
+        let by = if up { 1 } else { -1 };
+        match self.focus {
+            Focus::Slider(slider) => self.step(slider, up, fine),
+            Focus::Recent => self.pick_recent(nudge(self.recent, by, recent.len()), recent),
+            Focus::Named => self.pick_named(nudge(self.named, by, NAMED_COLORS.len())),
+        }
+    }
+
+    /// Handles a key, other than the Enter and Esc that close the picker. `recent` is the list
+    /// of recently picked colors. Returns true if the key was used.
+    pub fn handle_key(&mut self, key: KeyEvent, recent: &[[u8; 3]]) -> bool {
+        let fine = key.modifiers.contains(KeyModifiers::SHIFT);
+        match key.code {
+            KeyCode::Tab | KeyCode::BackTab => {
+                self.mode = match self.mode {
+                    PickerMode::Hsv => PickerMode::Rgb,
+                    PickerMode::Rgb => PickerMode::Hsv,
+                };
+            }
+            KeyCode::Up | KeyCode::Down => self.move_focus(key.code == KeyCode::Down, recent),
+            KeyCode::Left | KeyCode::Right => self.nudge(key.code == KeyCode::Right, fine, recent),
+            _ => return false,
+        }
+        true
+    }
+
+    /// Handles the scroll wheel like Left/Right.
+    pub fn scroll(&mut self, up: bool, fine: bool, recent: &[[u8; 3]]) {
+        self.nudge(up, fine, recent);
+    }
+
+    /// Sets a slider or picks a swatch at a clicked cell. Returns true if anything was there.
+    pub fn click(&mut self, column: u16, row: u16, recent: &[[u8; 3]]) -> bool {
+        let position = Position::new(column, row);
+        let target = self
+            .targets
+            .borrow()
+            .iter()
+            .find(|(area, _)| area.contains(position))
+            .map(|&(area, target)| (area, target));
+        match target {
+            Some((area, Target::Slider(slider))) => {
+                let t = f64::from(column - area.x) / f64::from(area.width.max(2) - 1);
+                self.focus = Focus::Slider(slider);
+                self.set_value(slider, t * self.max(slider));
+            }
+            Some((_, Target::Recent(index))) => self.pick_recent(index, recent),
+            Some((_, Target::Named(index))) => self.pick_named(index),
+            None => return false,
+        }
+        true
+    }
+
+    fn slider(&self, slider: usize, area: Rect, app: &App) -> Line<'static> {
+        let names = match self.mode {
+            PickerMode::Hsv => ["H", "S", "V"],
+            PickerMode::Rgb => ["R", "G", "B"],
+        };
+        let label_style = if self.focus == Focus::Slider(slider) {
+            Style::default()
+                .fg(app.theme.highlight)
+                .add_modifier(Modifier::BOLD)
+        } else {
+            Style::default().fg(app.theme.label)
+        };
+        let value = self.value(slider);
+        let unit = match (self.mode, slider) {
+            (PickerMode::Hsv, 0) => "°",
+            (PickerMode::Hsv, _) => "%",
+            (PickerMode::Rgb, _) => "",
+        };
+
+        let track = area.width.saturating_sub(9);
+        let last = f64::from(track.max(2) - 1);
+        let marker = (value / self.max(slider) * last).round();
+        let mut spans = vec![Span::styled(format!("{} ", names[slider]), label_style)];
+        for i in 0..track {
+            let (rgb, _) = self.with_value(slider, f64::from(i) / last * self.max(slider));
+            let style = Style::default().bg(to_color(rgb));
+            spans.push(if (f64::from(i) - marker).abs() < 0.5 {
+                Span::styled("┃", style.fg(contrast(rgb)))
+            } else {
+                Span::styled(" ", style)
+            });
+        }
+        spans.push(Span::raw(format!(" {value:>3.0}{unit}")));
+        self.targets.borrow_mut().push((
+            Rect::new(area.x + 2, area.y, track, 1),
+            Target::Slider(slider),
+        ));
+        Line::from(spans)
+    }
+
+    /// A row of swatches, numbered from `first`, with the focused one marked.
+    fn swatches(
+        &self,
+        colors: &[[u8; 3]],
+        first: usize,
+        focused: Option<usize>,
+        area: Rect,
+        target: fn(usize) -> Target,
+    ) -> Line<'static> {
+        let mut spans = Vec::new();
+        for (i, &rgb) in colors.iter().enumerate() {
+            let text = if focused == Some(first + i) {
+                "[ ]"
+            } else {
+                "   "
+            };
+            spans.push(Span::styled(
+                text,
+                Style::default().bg(to_color(rgb)).fg(contrast(rgb)),
+            ));
+            spans.push(Span::raw(" "));
+            let x = area.x + u16::try_from(i).unwrap_or(u16::MAX) * (SWATCH_WIDTH + 1);
+            self.targets
+                .borrow_mut()
+                .push((Rect::new(x, area.y, SWATCH_WIDTH, 1), target(first + i)));
+        }
+        Line::from(spans)
+    }
+
+    /// The named colors, wrapped onto as many rows as fit and scrolled to keep the focused one in
+    /// view.
+    fn render_named(&self, frame: &mut Frame, grid: Rect) {
+        let columns = usize::from((grid.width + 1) / (SWATCH_WIDTH + 1)).max(1);
+        self.named_columns.set(columns);
+        let first_row = (self.named / columns).saturating_sub(usize::from(grid.height.max(1)) - 1);
+        let focused = (self.focus == Focus::Named).then_some(self.named);
+        let rows = NAMED_COLORS.len().div_ceil(columns);
+        for (row, y) in (first_row..rows).zip(grid.y..grid.bottom()) {
+            let start = row * columns;
+            let colors: Vec<_> = NAMED_COLORS[start..(start + columns).min(NAMED_COLORS.len())]
+                .iter()
+                .map(|(_, rgb)| *rgb)
+                .collect();
+            let area = Rect::new(grid.x, y, grid.width, 1);
+            frame.render_widget(
+                self.swatches(&colors, start, focused, area, Target::Named),
+                area,
+            );
+        }
+    }
+}
+
+pub fn render_picker(frame: &mut Frame, area: Rect, app: &App) {
+    let Some(picker) = &app.picker else {
+        return;
+    };
+    picker.targets.borrow_mut().clear();
+    let mode = match picker.mode {
+        PickerMode::Hsv => "HSV",
+        PickerMode::Rgb => "RGB",
+    };
+    let block = Block::default()
+        .title(format!("Pick a color ({mode})"))
+        .title_bottom("Tab: HSV/RGB  ↑↓←→: Choose  Enter: Use  Esc: Cancel")
+        .title_alignment(Alignment::Center)
+        .title_style(
+            Style::default()
+                .fg(app.theme.title)
+                .add_modifier(Modifier::BOLD),
+        )
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .style(Style::default().bg(app.theme.popup));
+    let popup = centered_rect(70, 70, area);
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(5),
+            Constraint::Length(1),
+            Constraint::Length(1),
+            Constraint::Length(1),
+            Constraint::Length(1),
+            Constraint::Min(1),
+        ])
+        .margin(1)
+        .split(popup);
+    frame.render_widget(Clear, popup);
+    frame.render_widget(block, popup);
+
+    let top = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Length(12), Constraint::Min(10)])
+        .spacing(2)
+        .split(chunks[0]);
+    let swatch = Paragraph::new(vec![Line::raw(""), Line::raw(""), Line::raw(picker.hex())])
+        .alignment(Alignment::Center)
+        .style(
+            Style::default()
+                .bg(to_color(picker.rgb))
+                .fg(contrast(picker.rgb)),
+        );
+    frame.render_widget(swatch, top[0]);
+    let sliders = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(1); 3])
+        .spacing(1)
+        .split(top[1]);
+    for (slider, area) in sliders.iter().enumerate() {
+        frame.render_widget(picker.slider(slider, *area, app), *area);
+    }
+
+    let heading = |text: String, focused: bool| {
+        let style = Style::default().fg(app.theme.label);
+        Line::styled(
+            text,
+            if focused {
+                style.add_modifier(Modifier::BOLD)
+            } else {
+                style
+            },
+        )
+    };
+    let recent = if app.recent_colors.is_empty() {
+        "Recent: none yet".to_string()
+    } else {
+        "Recent".to_string()
+    };
+    frame.render_widget(heading(recent, picker.focus == Focus::Recent), chunks[2]);
+    let recent = picker.swatches(
+        &app.recent_colors,
+        0,
+        (picker.focus == Focus::Recent).then_some(picker.recent),
+        chunks[3],
+        Target::Recent,
+    );
+    frame.render_widget(recent, chunks[3]);
+
+    // name the color if it is one, even when it came from the sliders
+    let name = NAMED_COLORS
+        .iter()
+        .find(|(_, rgb)| *rgb == picker.rgb)
+        .map_or(String::new(), |(name, _)| format!(": {name}"));
+    frame.render_widget(
+        heading(format!("Named{name}"), picker.focus == Focus::Named),
+        chunks[4],
+    );
+
+    picker.render_named(frame, chunks[5]);
+}
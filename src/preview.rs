@@ -1,81 +1,651 @@
 #![warn(clippy::pedantic)]
 
-use std::{fs::File, io::Read};
+use std::{
+    fs::File,
+    hash::{DefaultHasher, Hash, Hasher},
+    io::Read,
+};
 
-use crate::App;
+use crate::{
+    app::{shown, CameraSettings, CurrentScreen, SceneMaterial, SceneObject, SceneSky},
+    dither,
+    graphics::{self, PendingImage},
+    projection::{Projection, Projector},
+    App,
+};
 use color_eyre::Result;
 use ratatui::{
     buffer::Buffer,
-    layout::{Margin, Rect},
+    layout::{Margin, Position, Rect},
     style::{Color, Modifier, Style},
+    text::Line,
     symbols::half_block::UPPER,
-    widgets::{block::BlockExt, canvas::Canvas, Block, BorderType, Borders, Widget},
+    widgets::{block::BlockExt, canvas::Canvas, Block, BorderType, Borders, Clear, Widget},
     Frame,
 };
 use rtwlib::{
     camera::{self, Camera},
     color::linear_to_gamma,
     hittable::HittableList,
+    ray::Ray,
     vec3::{Point3, Vec3},
 };
 
-pub fn render_preview(frame: &mut Frame, area: Rect, app: &App, block: bool) -> Result<()> {
-    let preview_block = Block::new()
-        .title("Preview")
-        .borders(Borders::ALL)
-        .border_type(BorderType::Rounded);
+/// Most pixels the preview will trace when drawing with an image protocol, terminals can be a lot
+/// bigger than a preview needs to be.
+const MAX_GRAPHICS_PIXELS: u32 = 120_000;
 
-    let mut cam = Camera::new();
-    cam.image_width = (area.width - 2).into();
-    cam.image_height = (area.height * 2).into();
-    cam.samples = 10;
-    cam.bounces = 5;
+/// How the preview gets drawn to the terminal.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PreviewMode {
+    /// `▀` glyphs with 24-bit colors, two pixels per cell
+    HalfBlocks,
+    /// Half blocks dithered down to the 256 color palette
+    Indexed256,
+    /// Half blocks dithered down to the 16 ANSI colors
+    Ansi16,
+    /// Monochrome braille dots, eight pixels per cell
+    Braille,
+    /// A ramp of ASCII characters by brightness, for when nothing else works
+    Ascii,
+    Kitty,
+    Sixel,
+    ITerm2,
+}
 
-    cam.sky = app.sky.clone();
+impl PreviewMode {
+    /// Picks the best mode the terminal supports. Kitty graphics and Sixel are asked for directly,
+    /// and its environment variables are the fallback when it doesn't answer. Needs raw mode, see
+    /// [`graphics::query`].
+    pub fn detect() -> Self {
+        let var = |name: &str| std::env::var(name).unwrap_or_default();
+        let (term, program, colorterm) = (var("TERM"), var("TERM_PROGRAM"), var("COLORTERM"));
+        let iterm2 =
+            program == "iTerm.app" || program == "WezTerm" || var("LC_TERMINAL") == "iTerm2";
 
-    cam.lookfrom = Point3::new(
-        app.camx.parse::<f64>()?,
-        app.camy.parse::<f64>()?,
-        app.camz.parse::<f64>()?,
-    );
+        // tmux needs passthrough set up for image protocols, so don't risk it
+        let graphics = if var("TMUX").is_empty() {
+            match graphics::query() {
+                Some(support) if support.kitty => Some(PreviewMode::Kitty),
+                Some(support) if support.sixel => Some(PreviewMode::Sixel),
+                // iTerm2 images can't be asked about, so they still go off the environment
+                Some(_) => iterm2.then_some(PreviewMode::ITerm2),
+                None => {
+                    if !var("KITTY_WINDOW_ID").is_empty()
+                        || term == "xterm-kitty"
+                        || program == "ghostty"
+                    {
+                        Some(PreviewMode::Kitty)
+                    } else if iterm2 {
+                        Some(PreviewMode::ITerm2)
+                    } else if term.contains("sixel")
+                        || term.starts_with("foot")
+                        || term.starts_with("mlterm")
+                        || program == "contour"
+                    {
+                        Some(PreviewMode::Sixel)
+                    } else {
+                        None
+                    }
+                }
+            }
+        } else {
+            None
+        };
 
-    cam.lookat = Point3::new(
-        app.lookx.parse::<f64>()?,
-        app.looky.parse::<f64>()?,
-        app.lookz.parse::<f64>()?,
-    );
+        if let Some(mode) = graphics {
+            mode
+        } else if colorterm == "truecolor" || colorterm == "24bit" {
+            PreviewMode::HalfBlocks
+        } else if term.contains("256color") {
+            PreviewMode::Indexed256
+        } else if term.is_empty() || term == "dumb" || term.starts_with("vt") {
+            PreviewMode::Ascii
+        } else {
+            // includes the linux console, which only has 16 colors
+            PreviewMode::Ansi16
+        }
+    }
 
-    cam.vup = Point3::new(0.0, 1.0, 0.0);
+    pub fn next(self) -> Self {
+        match self {
+            PreviewMode::HalfBlocks => PreviewMode::Indexed256,
+            PreviewMode::Indexed256 => PreviewMode::Ansi16,
+            PreviewMode::Ansi16 => PreviewMode::Braille,
+            PreviewMode::Braille => PreviewMode::Ascii,
+            PreviewMode::Ascii => PreviewMode::Kitty,
+            PreviewMode::Kitty => PreviewMode::Sixel,
+            PreviewMode::Sixel => PreviewMode::ITerm2,
+            PreviewMode::ITerm2 => PreviewMode::HalfBlocks,
+        }
+    }
 
-    cam.vfov = app.fov.parse::<f64>()?;
-    cam.focus_dist = app.focus_dist.parse::<f64>()?;
-    cam.defocus_angle = app.aperture.parse::<f64>()?;
-    let preview: Preview;
+    /// The size to trace the preview at to fill `area`.
+    pub fn resolution(self, area: Rect) -> (u32, u32) {
+        match self {
+            PreviewMode::HalfBlocks | PreviewMode::Indexed256 | PreviewMode::Ansi16 => {
+                (area.width.into(), u32::from(area.height) * 2)
+            }
+            PreviewMode::Braille => (u32::from(area.width) * 2, u32::from(area.height) * 4),
+            PreviewMode::Ascii => (area.width.into(), area.height.into()),
+            PreviewMode::Kitty | PreviewMode::Sixel | PreviewMode::ITerm2 => {
+                let (cell_w, cell_h) = graphics::cell_size();
+                let width = u32::from(area.width) * u32::from(cell_w);
+                let height = u32::from(area.height) * u32::from(cell_h);
+                let mut scale = 1;
+                while (width / scale) * (height / scale) > MAX_GRAPHICS_PIXELS {
+                    scale += 1;
+                }
+                ((width / scale).max(1), (height / scale).max(1))
+            }
+        }
+    }
+}
 
-    if block {
-        preview = Preview::new(app.world.clone())
-            .block(preview_block)
-            .camera(cam);
+impl std::fmt::Display for PreviewMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PreviewMode::HalfBlocks => write!(f, "Half blocks"),
+            PreviewMode::Indexed256 => write!(f, "256 colors"),
+            PreviewMode::Ansi16 => write!(f, "16 colors"),
+            PreviewMode::Braille => write!(f, "Braille"),
+            PreviewMode::Ascii => write!(f, "ASCII"),
+            PreviewMode::Kitty => write!(f, "Kitty"),
+            PreviewMode::Sixel => write!(f, "Sixel"),
+            PreviewMode::ITerm2 => write!(f, "iTerm2"),
+        }
+    }
+}
+
+/// Draws the preview, tracing it first if anything changed since the last time.
+pub fn render_preview(frame: &mut Frame, area: Rect, app: &App, block: bool) -> Result<()> {
+    draw_preview(frame, area, app, block, false)
+}
+
+/// Draws the preview next to the editors. Tracing happens in the background, and the last
+/// preview stays up until the new one is done.
+pub fn render_split_preview(frame: &mut Frame, area: Rect, app: &App) -> Result<()> {
+    draw_preview(frame, area, app, true, true)
+}
+
+fn draw_preview(
+    frame: &mut Frame,
+    area: Rect,
+    app: &App,
+    block: bool,
+    background: bool,
+) -> Result<()> {
+    let mut preview_block = Block::new()
+        .title(if app.shading == Shading::Full {
+            format!("Preview ({})", app.preview_mode)
+        } else {
+            format!("Preview ({}, {} shading)", app.preview_mode, app.shading)
+        })
+        .title_bottom(app.preview_quality.to_string())
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded);
+
+    let mut inner = if block {
+        preview_block.inner(area)
     } else {
-        preview = Preview::new(app.world.clone()).camera(cam);
+        area
+    };
+    // a sixel image touching the bottom of the screen scrolls the whole terminal up
+    if app.preview_mode == PreviewMode::Sixel && inner.bottom() >= frame.area().bottom() {
+        inner.height = inner.height.saturating_sub(1);
+    }
+    app.preview_area.set(inner);
+    if inner.is_empty() {
+        return Ok(());
+    }
+
+    let job = PreviewJob::new(app, inner)?;
+    let key = job.key();
+    let stale = app
+        .preview_cache
+        .borrow()
+        .as_ref()
+        .is_none_or(|cache| cache.key != key);
+    if stale && background {
+        app.preview_worker.borrow_mut().request(key, job);
+        preview_block = preview_block.title_bottom(Line::from(" tracing… ").right_aligned());
+    } else if stale {
+        *app.preview_cache.borrow_mut() = Some(job.run(key));
     }
 
+    let cache = app.preview_cache.borrow();
+    let lines = match cache.as_ref() {
+        Some(cache) => {
+            // the image has to be sent again every frame, or it gets cleared off the screen
+            if let Some(escape) = cache.escape.as_ref().filter(|_| cache.area == inner) {
+                app.graphics.borrow_mut().pending = Some(PendingImage {
+                    area: inner,
+                    mode: app.preview_mode,
+                    escape: escape.clone(),
+                });
+            }
+            cache.lines.clone()
+        }
+        None => Vec::new(),
+    };
+
+    let mut preview = Preview::new(lines, app.preview_mode);
+    if block {
+        preview = preview.block(preview_block);
+    }
     frame.render_widget(preview, area);
 
     Ok(())
 }
 
+/// How much work the preview puts into each frame, adjustable from the preview screen.
+#[derive(Clone, Copy)]
+pub struct PreviewQuality {
+    pub samples: u32,
+    pub bounces: u32,
+    /// The preview is traced at 1 / divisor of its full resolution and scaled up
+    pub divisor: u32,
+}
+
+impl Default for PreviewQuality {
+    fn default() -> Self {
+        Self {
+            samples: 10,
+            bounces: 5,
+            divisor: 1,
+        }
+    }
+}
+
+impl PreviewQuality {
+    /// Doubles or halves the samples per pixel.
+    pub fn step_samples(&mut self, up: bool) {
+        self.samples = if up {
+            self.samples * 2
+        } else {
+            self.samples / 2
+        }
+        .clamp(1, 512);
+    }
+
+    pub fn step_bounces(&mut self, up: bool) {
+        self.bounces = if up {
+            self.bounces + 1
+        } else {
+            self.bounces - 1
+        }
+        .clamp(1, 50);
+    }
+
+    /// Going up makes the preview coarser.
+    pub fn step_divisor(&mut self, up: bool) {
+        self.divisor = if up {
+            self.divisor + 1
+        } else {
+            self.divisor - 1
+        }
+        .clamp(1, 8);
+    }
+}
+
+impl std::fmt::Display for PreviewQuality {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} samples, {} bounces", self.samples, self.bounces)?;
+        if self.divisor > 1 {
+            write!(f, ", 1/{} resolution", self.divisor)?;
+        }
+        Ok(())
+    }
+}
+
+/// Scales pixel rows up to `width` x `height` by repeating pixels.
+fn upscale(lines: &[Vec<[u8; 3]>], width: u32, height: u32) -> Vec<Vec<[u8; 3]>> {
+    let (src_h, src_w) = (lines.len(), lines.first().map_or(0, Vec::len));
+    if src_w == 0 || src_h == 0 {
+        return Vec::new();
+    }
+    let (width, height) = (width as usize, height as usize);
+    (0..height)
+        .map(|y| {
+            let line = &lines[(y * src_h / height).min(src_h - 1)];
+            (0..width)
+                .map(|x| line[(x * src_w / width).min(src_w - 1)])
+                .collect()
+        })
+        .collect()
+}
+
+/// What the preview shows for each pixel. Everything but `Full` ignores lighting, and all but
+/// `Bounces` ignore materials too, to make problems with the scene itself easier to spot.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Shading {
+    /// The scene as it will render
+    Full,
+    /// Surface normals as colors, back faces in flat purple
+    Normals,
+    /// Distance from the camera, nearer is brighter
+    Depth,
+    /// The color each material gives the first surface hit, without lighting
+    Albedo,
+    /// A different color for every object
+    ObjectId,
+    /// How many times rays bounce before escaping, from black (none) to white (all of them)
+    Bounces,
+}
+
+impl Shading {
+    pub fn next(self) -> Self {
+        match self {
+            Shading::Full => Shading::Normals,
+            Shading::Normals => Shading::Depth,
+            Shading::Depth => Shading::Albedo,
+            Shading::Albedo => Shading::ObjectId,
+            Shading::ObjectId => Shading::Bounces,
+            Shading::Bounces => Shading::Full,
+        }
+    }
+}
+
+impl std::fmt::Display for Shading {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Shading::Full => write!(f, "Full"),
+            Shading::Normals => write!(f, "Normals"),
+            Shading::Depth => write!(f, "Depth"),
+            Shading::Albedo => write!(f, "Albedo"),
+            Shading::ObjectId => write!(f, "Object ID"),
+            Shading::Bounces => write!(f, "Bounces"),
+        }
+    }
+}
+
+/// The last traced preview, reused until something that changes it does.
+pub struct PreviewCache {
+    pub key: u64,
+    /// Where the preview was drawn
+    area: Rect,
+    lines: Vec<Vec<[u8; 3]>>,
+    /// The encoded image, for modes that use an image protocol
+    escape: Option<String>,
+}
+
+/// Everything needed to trace the preview, copied out of the app so it can be traced on another
+/// thread.
+pub struct PreviewJob {
+    objects: Vec<SceneObject>,
+    materials: Vec<SceneMaterial>,
+    camera: CameraSettings,
+    mode: PreviewMode,
+    area: Rect,
+    /// Full resolution for `area`, before the quality divisor
+    resolution: (u32, u32),
+    shading: Shading,
+    quality: PreviewQuality,
+    /// The selected and marked objects, by their index in the traced world
+    selected: Vec<usize>,
+}
+
+impl PreviewJob {
+    pub fn new(app: &App, area: Rect) -> Result<Self> {
+        let shown = shown(&app.objects);
+        let mut objects: Vec<SceneObject> = shown.iter().map(|&i| app.placed(i)).collect();
+        // an object that's still being typed into the editor shows up as well
+        if let CurrentScreen::Editor = app.current_screen {
+            objects.extend(app.pending_object());
+        }
+        // the selection is outlined by where it is among the objects that are traced
+        let selected = app
+            .selected_object
+            .into_iter()
+            .chain(app.targets())
+            .filter_map(|selected| shown.iter().position(|&i| i == selected))
+            .collect();
+        Ok(Self {
+            objects,
+            materials: app.materials.clone(),
+            camera: app.camera_settings()?,
+            mode: app.preview_mode,
+            area,
+            resolution: app.preview_mode.resolution(area),
+            shading: app.shading,
+            quality: app.preview_quality,
+            selected,
+        })
+    }
+
+    /// A hash of everything the preview depends on, two jobs with the same key give the same
+    /// preview.
+    pub fn key(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        // floats aren't `Hash`, so they're collected up and hashed as bits
+        let mut floats = Vec::new();
+        for object in &self.objects {
+            (object.kind, object.material).hash(&mut hasher);
+            let position = object.position;
+            floats.extend([object.size, position.x, position.y, position.z]);
+        }
+        for material in &self.materials {
+            material.kind.hash(&mut hasher);
+            let color = material.color;
+            floats.extend([color.x, color.y, color.z, material.other]);
+        }
+        let camera = &self.camera;
+        for point in [camera.lookfrom, camera.lookat] {
+            floats.extend([point.x, point.y, point.z]);
+        }
+        floats.extend([camera.vfov, camera.focus_dist, camera.defocus_angle, camera.ortho_width]);
+        match camera.sky {
+            SceneSky::Solid(color) => floats.extend([color.x, color.y, color.z]),
+            SceneSky::Gradient { start, end } => {
+                floats.extend([start.x, start.y, start.z, end.x, end.y, end.z]);
+            }
+        }
+        for float in floats {
+            float.to_bits().hash(&mut hasher);
+        }
+
+        camera.projection.hash(&mut hasher);
+        (self.mode, self.area, self.resolution, self.shading).hash(&mut hasher);
+        let quality = &self.quality;
+        (quality.samples, quality.bounces, quality.divisor).hash(&mut hasher);
+        self.selected.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    /// Traces the preview, and encodes it if the preview mode uses an image protocol.
+    pub fn run(self, key: u64) -> PreviewCache {
+        let (width, height) = self.resolution;
+        let quality = self.quality;
+        let mut cam = self.camera.camera();
+        cam.image_width = (width / quality.divisor).max(1);
+        cam.image_height = (height / quality.divisor).max(1);
+        cam.samples = quality.samples;
+        cam.bounces = quality.bounces;
+        let projector = self.camera.projector(cam);
+        let world = HittableList {
+            objects: self
+                .objects
+                .iter()
+                .map(|object| object.to_hittable(&self.materials))
+                .collect(),
+        };
+        let mut lines = trace(&projector, &world, self.shading);
+        if !self.selected.is_empty() {
+            highlight(&mut lines, &object_ids(&projector, &world), &self.selected);
+        }
+
+        // image protocols stretch the image themselves, the text modes need a pixel for every dot
+        let escape = graphics::encode(self.mode, &lines, self.area);
+        if escape.is_none() && quality.divisor > 1 {
+            lines = upscale(&lines, width, height);
+        }
+        PreviewCache {
+            key,
+            area: self.area,
+            lines,
+            escape,
+        }
+    }
+}
+
+/// Finds the object under a terminal cell in the preview, by casting a ray through the middle of it.
+pub fn pick(app: &App, column: u16, row: u16) -> Result<Option<usize>> {
+    let area = app.preview_area.get();
+    if !area.contains(Position::new(column, row)) {
+        return Ok(None);
+    }
+    let mut cam = app.camera()?;
+    (cam.image_width, cam.image_height) = app.preview_mode.resolution(area);
+    let x = (2 * u32::from(column - area.x) + 1) * cam.image_width / (2 * u32::from(area.width));
+    let y = (2 * u32::from(row - area.y) + 1) * cam.image_height / (2 * u32::from(area.height));
+    let projector = app.projector(cam)?;
+    let ray = projector.get_ray(x, y);
+    // the world only has the objects that are shown, so its indices are looked up in that list
+    Ok(Projector::closest_hit(&ray, &app.world()).map(|(index, _)| shown(&app.objects)[index]))
+}
+
+/// Traces the scene into rows of gamma corrected RGB pixels.
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn trace(projector: &Projector, world: &HittableList, shading: Shading) -> Vec<Vec<[u8; 3]>> {
+    let cam = &projector.cam;
+    let mut lines = Vec::new();
+    for y in 0..cam.get_height() {
+        let mut xlines = Vec::new();
+        for x in 0..cam.image_width {
+            let mut color = Vec3::new(0., 0., 0.);
+            for _ in 0..cam.samples {
+                //gets jittered rays per sample, averages result.
+                let r = projector.get_ray(x, y);
+                color += shade(projector, r, world, shading);
+            }
+            color = color * cam.get_sample_scale();
+
+            let color_r = linear_to_gamma(color.x);
+            let color_g = linear_to_gamma(color.y);
+            let color_b = linear_to_gamma(color.z);
+
+            let color_u8 = [
+                (color_r * 255.0) as u8,
+                (color_g * 255.0) as u8,
+                (color_b * 255.0) as u8,
+            ];
+            xlines.push(color_u8);
+        }
+        lines.push(xlines);
+    }
+    lines
+}
+
+/// Which object the primary ray through each pixel hits first.
+fn object_ids(projector: &Projector, world: &HittableList) -> Vec<Vec<Option<usize>>> {
+    let cam = &projector.cam;
+    (0..cam.get_height())
+        .map(|y| {
+            (0..cam.image_width)
+                .map(|x| {
+                    Projector::closest_hit(&projector.get_ray(x, y), world).map(|(index, _)| index)
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// Tints the pixels showing the `selected` objects, and outlines each of their edges.
+fn highlight(lines: &mut [Vec<[u8; 3]>], ids: &[Vec<Option<usize>>], selected: &[usize]) {
+    const TINT: [u8; 3] = [255, 170, 0];
+    let id_at = |x: usize, y: usize| ids.get(y).and_then(|row| row.get(x)).copied().flatten();
+    for (y, line) in lines.iter_mut().enumerate() {
+        for (x, pixel) in line.iter_mut().enumerate() {
+            let id = id_at(x, y);
+            if !id.is_some_and(|id| selected.contains(&id)) {
+                continue;
+            }
+            // selected objects that touch still get a line between them
+            let edge = x == 0
+                || y == 0
+                || id_at(x - 1, y) != id
+                || id_at(x + 1, y) != id
+                || id_at(x, y - 1) != id
+                || id_at(x, y + 1) != id;
+            if edge {
+                *pixel = TINT;
+            } else {
+                for c in 0..3 {
+                    pixel[c] = u8::try_from((u16::from(pixel[c]) * 2 + u16::from(TINT[c])) / 3)
+                        .unwrap_or(u8::MAX);
+                }
+            }
+        }
+    }
+}
+
+/// The color of a single sample, in linear space.
+#[allow(clippy::cast_precision_loss)]
+fn shade(projector: &Projector, r: Ray, world: &HittableList, shading: Shading) -> Vec3 {
+    let cam = &projector.cam;
+    if shading == Shading::Full {
+        return projector.ray_color(r, cam.bounces, world, &mut 0);
+    }
+    if shading == Shading::Bounces {
+        let mut rays = 0;
+        projector.ray_color(r, cam.bounces, world, &mut rays);
+        // the first ray isn't a bounce
+        let bounces = rays.saturating_sub(1) as f64 / f64::from(cam.bounces.max(2) - 1);
+        return heat(bounces.min(1.0));
+    }
+
+    let Some((index, rec)) = Projector::closest_hit(&r, world) else {
+        return Vec3::from(0.);
+    };
+    match shading {
+        Shading::Normals if !rec.front_face => Vec3::new(0.3, 0.0, 0.3),
+        Shading::Normals => (rec.normal + Vec3::from(1.)) * 0.5,
+        Shading::Depth => {
+            // falls off over the distance to the point the camera looks at
+            let scale = (cam.lookat - cam.lookfrom).length().max(0.001);
+            Vec3::from((-rec.t / (scale * 2.0)).exp())
+        }
+        Shading::Albedo => {
+            let mut attenuation = Vec3::from(1.);
+            let mut scattered = Ray::new(Vec3::from(0.), Vec3::from(0.));
+            rec.mat.scatter(&r, &rec, &mut attenuation, &mut scattered);
+            attenuation
+        }
+        Shading::ObjectId => hue(index as f64 * 0.618_034),
+        Shading::Full | Shading::Bounces => unreachable!(),
+    }
+}
+
+/// A fully saturated color for a hue from 0.0 to 1.0, wrapping around.
+fn hue(h: f64) -> Vec3 {
+    let h = h.fract() * 6.0;
+    Vec3::new(
+        ((h - 3.0).abs() - 1.0).clamp(0.0, 1.0),
+        (2.0 - (h - 2.0).abs()).clamp(0.0, 1.0),
+        (2.0 - (h - 4.0).abs()).clamp(0.0, 1.0),
+    )
+}
+
+/// Black through red and yellow to white, for `amount` from 0.0 to 1.0.
+fn heat(amount: f64) -> Vec3 {
+    Vec3::new(
+        (amount * 3.0).min(1.0),
+        (amount * 3.0 - 1.0).clamp(0.0, 1.0),
+        (amount * 3.0 - 2.0).clamp(0.0, 1.0),
+    )
+}
+
 pub struct Preview<'a> {
-    cam: Camera,
-    world: HittableList,
+    lines: Vec<Vec<[u8; 3]>>,
+    mode: PreviewMode,
     block: Option<Block<'a>>,
 }
 
 impl<'a> Preview<'a> {
-    fn new(world: HittableList) -> Self {
+    fn new(lines: Vec<Vec<[u8; 3]>>, mode: PreviewMode) -> Self {
         Self {
-            cam: Camera::default(),
-            world,
+            lines,
+            mode,
             block: None,
         }
     }
@@ -85,65 +655,108 @@ impl<'a> Preview<'a> {
         self.block = Some(block);
         self
     }
+}
 
-    pub fn camera(mut self, cam: Camera) -> Self {
-        self.cam = cam;
-        self
+impl Widget for Preview<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        self.block.render(area, buf);
+        let area = self.block.inner_if_some(area);
+
+        match self.mode {
+            PreviewMode::HalfBlocks => draw_half_blocks(area, buf, &self.lines),
+            PreviewMode::Indexed256 => {
+                draw_half_blocks_with(area, buf, &self.lines, dither::to_256);
+            }
+            PreviewMode::Ansi16 => draw_half_blocks_with(area, buf, &self.lines, dither::to_16),
+            PreviewMode::Braille => draw_braille(area, buf, &self.lines),
+            PreviewMode::Ascii => draw_ascii(area, buf, &self.lines),
+            // the image gets written over this after the frame is drawn
+            PreviewMode::Kitty | PreviewMode::Sixel | PreviewMode::ITerm2 => {
+                Clear.render(area, buf);
+            }
+        }
     }
 }
 
-impl<'a> Widget for Preview<'a> {
-    fn render(mut self, area: Rect, buf: &mut Buffer) {
-        self.cam.initialize();
-        let mut lines = Vec::new();
-        for y in 0..=self.cam.get_height() - 1 {
-            let mut xlines = Vec::new();
-            for x in 0..=self.cam.image_width - 1 {
-                let mut color = Vec3::new(0., 0., 0.);
-                for _ in 0..self.cam.samples {
-                    //gets jittered rays per sample, averages result.
-                    let r = self.cam.get_ray(x.into(), y.into());
-                    color += self.cam.ray_color(r, 5, &self.world);
-                    //println!("{:?}\n{:?}", r, self.cam.ray_color(r, 5, &self.world));
-                }
-                color = color * self.cam.get_sample_scale();
-
-                let color_r = linear_to_gamma(color.x);
-                let color_g = linear_to_gamma(color.y);
-                let color_b = linear_to_gamma(color.z);
-
-                let color_u8 = [
-                    (color_r * 255.0) as u8,
-                    (color_g * 255.0) as u8,
-                    (color_b * 255.0) as u8,
-                ];
-                xlines.push(color_u8);
+/// Draws rows of RGB pixels into `area`, two rows to a cell. Each cell is a `▀` with the top pixel
+/// as the foreground and the bottom pixel as the background. Pixels outside `area` are cut off.
+pub fn draw_half_blocks(area: Rect, buf: &mut Buffer, lines: &[Vec<[u8; 3]>]) {
+    draw_half_blocks_with(area, buf, lines, |pixel, _, _| {
+        Color::Rgb(pixel[0], pixel[1], pixel[2])
+    });
+}
+
+/// Like [`draw_half_blocks`], but each pixel's terminal color comes from `to_color(pixel, x, y)`.
+fn draw_half_blocks_with<F>(area: Rect, buf: &mut Buffer, lines: &[Vec<[u8; 3]>], to_color: F)
+where
+    F: Fn([u8; 3], usize, usize) -> Color,
+{
+    for (row, pair) in lines.chunks(2).enumerate() {
+        if row >= area.height.into() {
+            break;
+        }
+        let top = &pair[0];
+        let bottom = pair.get(1);
+        for (x, upper) in top.iter().enumerate() {
+            if x >= area.width.into() {
+                //put this back if resizing starts crashing stuff
+                break;
             }
-            lines.push(xlines);
+            let lower = bottom.and_then(|line| line.get(x)).unwrap_or(&[0, 0, 0]);
+
+            buf.set_string(
+                area.left() + u16::try_from(x).unwrap_or(u16::MAX),
+                area.top() + u16::try_from(row).unwrap_or(u16::MAX),
+                "▀",
+                Style::default()
+                    .fg(to_color(*upper, x, row * 2))
+                    .bg(to_color(*lower, x, row * 2 + 1)),
+            );
         }
-        self.block.render(area, buf);
-        let area = self.block.inner_if_some(area);
+    }
+}
 
-        for y in (0..=lines.len() as usize - 1).step_by(2) {
-            for x in 0..=lines[y].len() as usize - 1 {
-                if (y / 2 >= area.height.into()) | (x >= lines[y].len()) {
-                    //put this back if resizing starts crashing stuff
-                    break;
+/// Draws pixels as braille dots, a 2x4 block of pixels per cell, lit where the pixel is bright.
+fn draw_braille(area: Rect, buf: &mut Buffer, lines: &[Vec<[u8; 3]>]) {
+    // bit for the dot at (x, y) within a cell
+    const DOTS: [[u32; 2]; 4] = [[0x01, 0x08], [0x02, 0x10], [0x04, 0x20], [0x40, 0x80]];
+    for row in 0..area.height {
+        for col in 0..area.width {
+            let mut bits = 0;
+            for (dy, dots) in DOTS.iter().enumerate() {
+                for (dx, dot) in dots.iter().enumerate() {
+                    let (x, y) = (usize::from(col) * 2 + dx, usize::from(row) * 4 + dy);
+                    let Some(pixel) = lines.get(y).and_then(|line| line.get(x)) else {
+                        continue;
+                    };
+                    if dither::luminance(*pixel) > 0.5 + dither::threshold(x, y) {
+                        bits |= dot;
+                    }
                 }
-
-                buf.set_string(
-                    area.left() + x as u16,
-                    area.top() + (y / 2) as u16,
-                    "▀",
-                    Style::default()
-                        .fg(Color::Rgb(lines[y][x][0], lines[y][x][1], lines[y][x][2]))
-                        .bg(Color::Rgb(
-                            lines[y + 1][x][0],
-                            lines[y + 1][x][1],
-                            lines[y + 1][x][2],
-                        )),
-                );
             }
+            let glyph = char::from_u32(0x2800 + bits).unwrap_or(' ');
+            buf[(area.left() + col, area.top() + row)].set_char(glyph);
+        }
+    }
+}
+
+/// Draws one pixel per cell as a character picked by brightness.
+#[allow(
+    clippy::cast_possible_truncation,
+    clippy::cast_sign_loss,
+    clippy::cast_precision_loss
+)]
+fn draw_ascii(area: Rect, buf: &mut Buffer, lines: &[Vec<[u8; 3]>]) {
+    const RAMP: &[u8] = b" .:-=+*#%@";
+    for (y, line) in lines.iter().enumerate().take(area.height.into()) {
+        for (x, pixel) in line.iter().enumerate().take(area.width.into()) {
+            let level = (dither::luminance(*pixel) * RAMP.len() as f64) as usize;
+            let glyph = char::from(RAMP[level.min(RAMP.len() - 1)]);
+            buf[(
+                area.left() + u16::try_from(x).unwrap_or(u16::MAX),
+                area.top() + u16::try_from(y).unwrap_or(u16::MAX),
+            )]
+                .set_char(glyph);
         }
     }
 }
@@ -0,0 +1,130 @@
+#![warn(clippy::pedantic)]
+
+use rand::Rng;
+use rtwlib::{
+    camera::Camera,
+    color::Color,
+    hittable::{HitRecord, Hittable, HittableList},
+    ray::Ray,
+    vec3::{cross, Vec3},
+};
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Projection {
+    Perspective,
+    Orthographic,
+}
+
+impl std::fmt::Display for Projection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Projection::Perspective => write!(f, "Perspective"),
+            Projection::Orthographic => write!(f, "Orthographic"),
+        }
+    }
+}
+
+/// Wraps a `Camera` so it can shoot rays with either projection.
+/// The library camera only does perspective, and keeps its basis vectors private, so the
+/// orthographic rays are built here from `lookfrom`, `lookat` and `vup`.
+pub struct Projector {
+    pub cam: Camera,
+    projection: Projection,
+    /// Width of the view in world units, only used by orthographic projection
+    ortho_width: f64,
+    u: Vec3,
+    v: Vec3,
+    w: Vec3,
+}
+
+impl Projector {
+    pub fn new(mut cam: Camera, projection: Projection, ortho_width: f64) -> Self {
+        cam.initialize();
+        let w = (cam.lookfrom - cam.lookat).normalized();
+        let u = cross(&cam.vup, &w).normalized();
+        let v = cross(&w, &u);
+        Self {
+            cam,
+            projection,
+            ortho_width,
+            u,
+            v,
+            w,
+        }
+    }
+
+    /// Creates a jittered ray through pixel (i, j), like `Camera::get_ray`.
+    pub fn get_ray(&self, i: u32, j: u32) -> Ray {
+        match self.projection {
+            Projection::Perspective => self.cam.get_ray(i, j),
+            Projection::Orthographic => {
+                let mut rng = rand::thread_rng();
+                let ortho_height = self.ortho_width / self.cam.aspect_ratio;
+                let x = (f64::from(i) + rng.gen_range(0.0..1.0)) / f64::from(self.cam.image_width);
+                let y = (f64::from(j) + rng.gen_range(0.0..1.0)) / f64::from(self.cam.image_height);
+                let origin = self.cam.lookfrom + (x - 0.5) * self.ortho_width * self.u
+                    - (y - 0.5) * ortho_height * self.v;
+                Ray::new(origin, -self.w)
+            }
+        }
+    }
+
+    /// Traces a ray through the scene like `Camera::ray_color`, counting every ray traced into `rays`.
+    pub fn ray_color(&self, r: Ray, bounces: u32, world: &HittableList, rays: &mut u64) -> Color {
+        if bounces == 0 {
+            return Color::from(0.);
+        }
+        *rays += 1;
+
+        let mut rec = HitRecord::default();
+        if world.hit(&r, 0.001..f64::INFINITY, &mut rec) {
+            let mut scattered = Ray::new(Vec3::from(0.), Vec3::from(0.));
+            let mut attenuation = Color::from(1.);
+
+            if rec.mat.scatter(&r, &rec, &mut attenuation, &mut scattered) {
+                return attenuation * self.ray_color(scattered, bounces - 1, world, rays);
+            }
+            return Color::from(0.);
+        }
+        self.cam.sky.color(r)
+    }
+
+    /// Finds the nearest hit along `r`, along with the index of the object that was hit.
+    /// `HitRecord` doesn't say which object it came from, so each one is tested on its own.
+    pub fn closest_hit(r: &Ray, world: &HittableList) -> Option<(usize, HitRecord)> {
+        let mut closest = None;
+        let mut nearest = f64::INFINITY;
+        for (index, object) in world.objects.iter().enumerate() {
+            let mut rec = HitRecord::default();
+            if object.hit(r, 0.001..nearest, &mut rec) {
+                nearest = rec.t;
+                closest = Some((index, rec));
+            }
+        }
+        closest
+    }
+
+    /// Renders the scene to a buffer of RGB bytes, like `Camera::render_to_bytes`.
+    /// Runs the progress callback every line with the row number and the number of rays it took.
+    pub fn render_to_bytes<F>(&self, world: &HittableList, mut progress: F) -> Vec<u8>
+    where
+        F: FnMut(u32, u64),
+    {
+        let mut buffer = Vec::new();
+        for j in 0..self.cam.image_height {
+            let mut rays = 0;
+            for i in 0..self.cam.image_width {
+                let mut pixel_color = Color::from(0.0);
+                for _ in 0..self.cam.samples {
+                    let r = self.get_ray(i, j);
+                    pixel_color += self.ray_color(r, self.cam.bounces, world, &mut rays);
+                }
+                buffer.extend_from_slice(
+                    &(pixel_color * self.cam.get_sample_scale()).to_rgb_bytes(),
+                );
+            }
+            progress(j, rays);
+        }
+        buffer
+    }
+}
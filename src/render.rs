@@ -1,9 +1,14 @@
 #![warn(clippy::pedantic)]
 
-use std::{fs::File, io::Write};
+use std::{
+    fs::File,
+    io::Write,
+    time::{Duration, Instant},
+};
 
 use color_eyre::Result;
 use crossterm::terminal;
+use serde_json::json;
 use ratatui::{
     layout::{Alignment, Constraint, Direction, Layout, Rect},
     prelude::Backend,
@@ -18,7 +23,12 @@ use rtwlib::{
     vec3::Point3,
 };
 
-use crate::{centered_rect, App, CurrentlyEditing};
+use crate::{
+    app::shown,
+    centered_rect,
+    form::{render_row, FormStyle, RENDER_FORM},
+    App,
+};
 
 pub fn render_view(frame: &mut Frame, area: Rect, app: &App) {
     // set settings for render
@@ -52,128 +62,6 @@ pub fn render_view(frame: &mut Frame, area: Rect, app: &App) {
         ])
         .margin(1)
         .split(render_popup_area);
-    let image_chunks = Layout::default()
-        .direction(Direction::Horizontal)
-        .constraints(vec![
-            Constraint::Min(5),  //width
-            Constraint::Min(5),  //height
-            Constraint::Min(10), //filename
-        ])
-        .spacing(1)
-        .split(render_chunks[1]);
-    let quality_chunks = Layout::default()
-        .direction(Direction::Horizontal)
-        .constraints(vec![Constraint::Min(6), Constraint::Min(6)])
-        .spacing(1)
-        .split(render_chunks[3]);
-    let camera_position_chunks = Layout::default()
-        .direction(Direction::Horizontal)
-        .constraints(vec![
-            Constraint::Min(6), //x
-            Constraint::Min(6), //y
-            Constraint::Min(6), //z
-            Constraint::Min(6), //lookx
-            Constraint::Min(6), //looky
-            Constraint::Min(6), //lookz
-        ])
-        .spacing(1)
-        .split(render_chunks[5]);
-    let camera_settings_chunks = Layout::default()
-        .direction(Direction::Horizontal)
-        .constraints(vec![
-            Constraint::Min(6), //fov
-            Constraint::Min(6), //focus_dist
-            Constraint::Min(6), //aperture
-        ])
-        .spacing(1)
-        .split(render_chunks[6]);
-
-    let base_block = Block::default()
-        .borders(Borders::ALL)
-        .border_type(BorderType::Plain);
-
-    let mut width_block = base_block.clone().title("Image Width");
-    let mut height_block = base_block.clone().title("Image Height");
-    let mut filename_block = base_block.clone().title("Filename");
-    let mut samples_block = base_block.clone().title("Samples");
-    let mut bounces_block = base_block.clone().title("Bounces");
-    let mut camx_block = base_block.clone().title("Camera X");
-    let mut camy_block = base_block.clone().title("Camera Y");
-    let mut camz_block = base_block.clone().title("Camera Z");
-    let mut lookx_block = base_block.clone().title("Look X");
-    let mut looky_block = base_block.clone().title("Look Y");
-    let mut lookz_block = base_block.clone().title("Look Z");
-    let mut fov_block = base_block.clone().title("FOV");
-    let mut focus_dist_block = base_block.clone().title("Focus Distance");
-    let mut aperture_block = base_block.clone().title("Blur amount");
-
-    let style = Style::default().bold();
-
-    if let Some(editing) = &app.current_edit {
-        match editing {
-            CurrentlyEditing::Width => {
-                width_block = width_block.border_type(BorderType::Double).style(style)
-            }
-            CurrentlyEditing::Height => {
-                height_block = height_block.border_type(BorderType::Double).style(style)
-            }
-            CurrentlyEditing::ImgName => {
-                filename_block = filename_block.border_type(BorderType::Double).style(style)
-            }
-            CurrentlyEditing::Samples => {
-                samples_block = samples_block.border_type(BorderType::Double).style(style)
-            }
-            CurrentlyEditing::Bounces => {
-                bounces_block = bounces_block.border_type(BorderType::Double).style(style)
-            }
-            CurrentlyEditing::CamX => {
-                camx_block = camx_block.border_type(BorderType::Double).style(style)
-            }
-            CurrentlyEditing::CamY => {
-                camy_block = camy_block.border_type(BorderType::Double).style(style)
-            }
-            CurrentlyEditing::CamZ => {
-                camz_block = camz_block.border_type(BorderType::Double).style(style)
-            }
-            CurrentlyEditing::LookX => {
-                lookx_block = lookx_block.border_type(BorderType::Double).style(style)
-            }
-            CurrentlyEditing::LookY => {
-                looky_block = looky_block.border_type(BorderType::Double).style(style)
-            }
-            CurrentlyEditing::LookZ => {
-                lookz_block = lookz_block.border_type(BorderType::Double).style(style)
-            }
-            CurrentlyEditing::Fov => {
-                fov_block = fov_block.border_type(BorderType::Double).style(style)
-            }
-            CurrentlyEditing::FocusDist => {
-                focus_dist_block = focus_dist_block
-                    .border_type(BorderType::Double)
-                    .style(style)
-            }
-            CurrentlyEditing::Aperture => {
-                aperture_block = aperture_block.border_type(BorderType::Double).style(style)
-            }
-            _ => {}
-        }
-    };
-
-    let txt_width = Paragraph::new(app.image_width.clone()).block(width_block);
-    let txt_height = Paragraph::new(app.image_height.clone()).block(height_block);
-    let txt_filename = Paragraph::new(app.image_name_input.clone()).block(filename_block);
-    let txt_samples = Paragraph::new(app.samples.clone()).block(samples_block);
-    let txt_bounces = Paragraph::new(app.bounces.clone()).block(bounces_block);
-    let txt_camx = Paragraph::new(app.camx.clone()).block(camx_block);
-    let txt_camy = Paragraph::new(app.camy.clone()).block(camy_block);
-    let txt_camz = Paragraph::new(app.camz.clone()).block(camz_block);
-    let txt_lookx = Paragraph::new(app.lookx.clone()).block(lookx_block);
-    let txt_looky = Paragraph::new(app.looky.clone()).block(looky_block);
-    let txt_lookz = Paragraph::new(app.lookz.clone()).block(lookz_block);
-    let txt_fov = Paragraph::new(app.fov.clone()).block(fov_block);
-    let txt_focus_dist = Paragraph::new(app.focus_dist.clone()).block(focus_dist_block);
-    let txt_aperture = Paragraph::new(app.aperture.clone()).block(aperture_block);
-
     let txt_render = Paragraph::new("Edit the settings below, and then hit ENTER to render")
         .style(Style::default().add_modifier(Modifier::BOLD));
     let txt_quality =
@@ -186,60 +74,136 @@ pub fn render_view(frame: &mut Frame, area: Rect, app: &App) {
     frame.render_widget(txt_quality, render_chunks[2]);
     frame.render_widget(txt_camera, render_chunks[4]);
 
-    frame.render_widget(txt_width, image_chunks[0]);
-    frame.render_widget(txt_height, image_chunks[1]);
-    frame.render_widget(txt_filename, image_chunks[2]);
-    frame.render_widget(txt_samples, quality_chunks[0]);
-    frame.render_widget(txt_bounces, quality_chunks[1]);
-    frame.render_widget(txt_camx, camera_position_chunks[0]);
-    frame.render_widget(txt_camy, camera_position_chunks[1]);
-    frame.render_widget(txt_camz, camera_position_chunks[2]);
-    frame.render_widget(txt_lookx, camera_position_chunks[3]);
-    frame.render_widget(txt_looky, camera_position_chunks[4]);
-    frame.render_widget(txt_lookz, camera_position_chunks[5]);
-    frame.render_widget(txt_fov, camera_settings_chunks[0]);
-    frame.render_widget(txt_focus_dist, camera_settings_chunks[1]);
-    frame.render_widget(txt_aperture, camera_settings_chunks[2]);
+    for (row, chunk) in RENDER_FORM.iter().zip([1, 3, 5, 6]) {
+        render_row(frame, render_chunks[chunk], app, row, FormStyle::Boxed);
+    }
 }
 
-pub fn render_image<B: Backend>(app: &mut App, terminal: &mut Terminal<B>) -> Result<()> {
-    // render image
-    let mut file = File::create(format!("{}.ppm", app.image_name_input))?;
-    let mut cam = Camera::new();
-    cam.aspect_ratio = app.image_width.parse::<f64>()? / app.image_height.parse::<f64>()?;
-    cam.image_width = app.image_width.parse::<u32>()?;
-    cam.samples = app.samples.parse::<u32>()?;
-    cam.bounces = app.bounces.parse::<u32>()?;
+/// Timing and ray counts collected while an image renders.
+pub struct RenderStats {
+    pub started: Instant,
+    pub rows_done: u32,
+    pub total_rows: u32,
+    /// Rays traced so far, bounces included
+    pub rays: u64,
+    pub samples_per_row: u64,
+    pub row_times: Vec<Duration>,
+}
+
+impl RenderStats {
+    pub fn new(cam: &Camera) -> Self {
+        Self {
+            started: Instant::now(),
+            rows_done: 0,
+            total_rows: cam.image_height,
+            rays: 0,
+            samples_per_row: u64::from(cam.image_width) * u64::from(cam.samples),
+            row_times: Vec::new(),
+        }
+    }
 
-    cam.lookfrom = Point3::new(
-        app.camx.parse::<f64>()?,
-        app.camy.parse::<f64>()?,
-        app.camz.parse::<f64>()?,
-    );
+    pub fn samples(&self) -> u64 {
+        self.samples_per_row * u64::from(self.rows_done)
+    }
 
-    cam.lookat = Point3::new(
-        app.lookx.parse::<f64>()?,
-        app.looky.parse::<f64>()?,
-        app.lookz.parse::<f64>()?,
-    );
+    #[allow(clippy::cast_precision_loss)]
+    pub fn samples_per_sec(&self) -> f64 {
+        self.samples() as f64 / self.started.elapsed().as_secs_f64().max(f64::EPSILON)
+    }
 
-    cam.vup = Point3::new(0.0, 1.0, 0.0);
+    /// Time left, guessed from the average row time so far.
+    pub fn eta(&self) -> Option<Duration> {
+        if self.rows_done == 0 {
+            return None;
+        }
+        let per_row = self.started.elapsed() / self.rows_done;
+        Some(per_row * (self.total_rows - self.rows_done))
+    }
+}
 
-    cam.vfov = app.fov.parse::<f64>()?;
-    cam.focus_dist = app.focus_dist.parse::<f64>()?;
-    cam.defocus_angle = app.aperture.parse::<f64>()?;
+/// Formats a duration as `m:ss`, or `h:mm:ss` for long renders.
+fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    if secs >= 3600 {
+        format!("{}:{:02}:{:02}", secs / 3600, secs / 60 % 60, secs % 60)
+    } else {
+        format!("{}:{:02}", secs / 60, secs % 60)
+    }
+}
 
-    cam.sky = app.sky.clone();
+pub fn render_image<B: Backend>(app: &mut App, terminal: &mut Terminal<B>) -> Result<()> {
+    // render image
+    let mut file = File::create(format!("{}.ppm", app.image_name_input))?;
+    let mut cam = app.camera()?;
+    (cam.image_width, cam.image_height) = app.render_size()?;
+    cam.samples = app.samples.parse::<u32>()?;
+    cam.bounces = app.bounces.parse::<u32>()?;
+    let projector = app.projector(cam)?;
+    let cam = &projector.cam;
 
-    let render = cam.render_to_bytes(app.world.clone(), |progress| {
+    app.render_stats = Some(RenderStats::new(cam));
+    let mut row_started = Instant::now();
+    let render = projector.render_to_bytes(&app.world(), |row, rays| {
+        if let Some(stats) = &mut app.render_stats {
+            stats.rows_done = row + 1;
+            stats.rays += rays;
+            stats.row_times.push(row_started.elapsed());
+        }
+        row_started = Instant::now();
         app.render_progress =
-            (progress as f64 / app.image_height.parse::<f64>().unwrap()).clamp(0.0, 1.0);
+            (f64::from(row + 1) / f64::from(cam.image_height)).clamp(0.0, 1.0);
         let _ = terminal.draw(|f| {
             progress_ui(f, app);
         });
     });
-    file.write(format!("P6\n{} {}\n255\n", cam.image_width, cam.get_height()).as_bytes())?;
+    file.write_all(format!("P6\n{} {}\n255\n", cam.image_width, cam.get_height()).as_bytes())?;
     file.write_all(&render)?;
+    write_render_report(app, cam)?;
+    Ok(())
+}
+
+/// Writes the settings and stats of the last render to a `.json` file next to the image.
+fn write_render_report(app: &App, cam: &Camera) -> Result<()> {
+    let Some(stats) = &app.render_stats else {
+        return Ok(());
+    };
+    let elapsed = stats.started.elapsed().as_secs_f64();
+    let row_ms: Vec<f64> = stats
+        .row_times
+        .iter()
+        .map(|time| time.as_secs_f64() * 1000.0)
+        .collect();
+
+    let report = json!({
+        "image": format!("{}.ppm", app.image_name_input),
+        "settings": {
+            "width": cam.image_width,
+            "height": cam.image_height,
+            "scale_percent": app.render_scale.parse::<f64>().ok(),
+            "samples": cam.samples,
+            "bounces": cam.bounces,
+            "camera": {
+                "position": [cam.lookfrom.x, cam.lookfrom.y, cam.lookfrom.z],
+                "look_at": [cam.lookat.x, cam.lookat.y, cam.lookat.z],
+                "fov": cam.vfov,
+                "focus_distance": cam.focus_dist,
+                "aperture": cam.defocus_angle,
+                "projection": app.projection.to_string(),
+                "ortho_width": app.ortho_width.parse::<f64>().ok(),
+            },
+            "objects": shown(&app.objects).len(),
+            "materials": app.materials.len(),
+        },
+        "stats": {
+            "elapsed_seconds": elapsed,
+            "rays_traced": stats.rays,
+            "samples": stats.samples(),
+            "samples_per_second": stats.samples_per_sec(),
+            "row_ms": row_ms,
+        },
+    });
+    let mut file = File::create(format!("{}.json", app.image_name_input))?;
+    file.write_all(serde_json::to_string_pretty(&report)?.as_bytes())?;
     Ok(())
 }
 
@@ -250,13 +214,44 @@ fn progress_ui(frame: &mut Frame, app: &App) {
         .border_type(BorderType::Double)
         .title_alignment(Alignment::Center)
         .title_style(Style::default().add_modifier(Modifier::BOLD));
-    let progress_popup_area = centered_rect(70, 20, frame.area());
+    let progress_popup_area = centered_rect(70, 30, frame.area());
+    let progress_chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(vec![Constraint::Length(1), Constraint::Min(1)])
+        .spacing(1)
+        .split(progress_block.inner(progress_popup_area));
 
     let progress_gauge = Gauge::default()
-        .block(progress_block)
-        .gauge_style(Style::new().white().on_black().bold())
+        .gauge_style(Style::new().fg(app.theme.text).bg(app.theme.popup).bold())
         .ratio(app.render_progress)
         .use_unicode(true)
         .label(format!("{:.2}%", app.render_progress * 100.0));
-    frame.render_widget(progress_gauge, progress_popup_area);
+
+    let mut stats_lines = Vec::new();
+    if let Some(stats) = &app.render_stats {
+        stats_lines.push(format!(
+            "Elapsed {}  |  ETA {}",
+            format_duration(stats.started.elapsed()),
+            stats.eta().map_or(String::from("--:--"), format_duration),
+        ));
+        stats_lines.push(format!(
+            "{} rays traced  |  {:.0} samples/sec",
+            stats.rays,
+            stats.samples_per_sec(),
+        ));
+        if let Some(last) = stats.row_times.last() {
+            stats_lines.push(format!(
+                "Row {}/{} took {}ms",
+                stats.rows_done,
+                stats.total_rows,
+                last.as_millis(),
+            ));
+        }
+    }
+    let txt_stats = Paragraph::new(stats_lines.join("\n")).alignment(Alignment::Center);
+
+    frame.render_widget(Clear, progress_popup_area);
+    frame.render_widget(progress_block, progress_popup_area);
+    frame.render_widget(progress_gauge, progress_chunks[0]);
+    frame.render_widget(txt_stats, progress_chunks[1]);
 }
@@ -0,0 +1,238 @@
+#![warn(clippy::pedantic)]
+//! Colors for the app's own UI, as opposed to the scene. The dark theme is the default, and
+//! `theme.json` in the config dir can pick another one and change any of its colors, for example
+//! `{ "base": "light", "header": "#c8d2f0", "title": "red" }`. Colors are names like `red` and
+//! `lightblue`, `#rrggbb` hex, or a number from the 256 color palette.
+
+use std::{fs, str::FromStr};
+
+use color_eyre::{
+    eyre::{eyre, WrapErr},
+    Result,
+};
+use ratatui::style::Color;
+
+use crate::config::config_dir;
+
+#[derive(Clone, Copy)]
+pub struct Theme {
+    /// Plain text on the theme's backgrounds
+    pub text: Color,
+    /// Hints that shouldn't stand out
+    pub muted: Color,
+    /// Names next to values, like in the inspector
+    pub label: Color,
+    /// Screen and popup titles
+    pub title: Color,
+    /// Headings inside a pane, like the sidebar and inspector sections
+    pub heading: Color,
+    /// Secondary headings and counts
+    pub accent: Color,
+    /// Names and the selected object on the map
+    pub highlight: Color,
+    /// Warnings, like the quit confirmation
+    pub warning: Color,
+    /// Titles of fields that can't be used yet
+    pub error: Color,
+    /// Background of every other table row, starting with the first
+    pub row: Color,
+    pub row_alt: Color,
+    pub row_selected: Color,
+    /// Background of rows picked out for bulk changes
+    pub row_marked: Color,
+    /// Background of the table header
+    pub header: Color,
+    /// Background of the popup editors
+    pub popup: Color,
+    /// Background of text fields in the popup editors
+    pub field: Color,
+    /// Background and text of the focused field in the popup editors
+    pub field_focused: Color,
+    pub field_focused_text: Color,
+    /// Selected text in a field
+    pub selection: Color,
+    pub selection_text: Color,
+    /// Background of color fields that aren't focused, with the color as the text
+    pub swatch: Color,
+    /// Stands in for a color field's color until it's valid
+    pub swatch_invalid: Color,
+    /// The camera and its view on the map
+    pub camera: Color,
+    pub camera_view: Color,
+}
+
+/// The built in themes, by the name `base` uses.
+const THEMES: &[(&str, Theme)] = &[
+    ("dark", Theme::DARK),
+    ("light", Theme::LIGHT),
+    ("high_contrast", Theme::HIGH_CONTRAST),
+];
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::DARK
+    }
+}
+
+impl Theme {
+    pub const DARK: Self = Self {
+        text: Color::White,
+        muted: Color::DarkGray,
+        label: Color::Gray,
+        title: Color::Red,
+        heading: Color::LightBlue,
+        accent: Color::Green,
+        highlight: Color::LightYellow,
+        warning: Color::LightRed,
+        error: Color::Red,
+        row: Color::Rgb(30, 30, 40),
+        row_alt: Color::Rgb(25, 25, 35),
+        row_selected: Color::Rgb(45, 45, 55),
+        row_marked: Color::Rgb(35, 50, 80),
+        header: Color::Rgb(30, 40, 75),
+        popup: Color::Black,
+        field: Color::DarkGray,
+        field_focused: Color::White,
+        field_focused_text: Color::Black,
+        selection: Color::LightBlue,
+        selection_text: Color::Black,
+        swatch: Color::Black,
+        swatch_invalid: Color::Magenta,
+        camera: Color::Cyan,
+        camera_view: Color::LightBlue,
+    };
+
+    /// For terminals with a light background, where the dark row stripes and light text of the
+    /// default theme are hard to read.
+    pub const LIGHT: Self = Self {
+        text: Color::Black,
+        muted: Color::Rgb(110, 110, 120),
+        label: Color::Rgb(80, 80, 90),
+        title: Color::Rgb(180, 30, 30),
+        heading: Color::Rgb(30, 70, 170),
+        accent: Color::Rgb(20, 120, 40),
+        highlight: Color::Rgb(150, 90, 0),
+        warning: Color::Rgb(190, 40, 40),
+        error: Color::Rgb(200, 0, 0),
+        row: Color::Rgb(235, 235, 242),
+        row_alt: Color::Rgb(245, 245, 250),
+        row_selected: Color::Rgb(205, 210, 230),
+        row_marked: Color::Rgb(215, 228, 250),
+        header: Color::Rgb(190, 205, 240),
+        popup: Color::Rgb(250, 250, 250),
+        field: Color::Rgb(220, 220, 225),
+        field_focused: Color::Rgb(40, 40, 50),
+        field_focused_text: Color::White,
+        selection: Color::Rgb(120, 160, 230),
+        selection_text: Color::Black,
+        swatch: Color::Rgb(220, 220, 225),
+        swatch_invalid: Color::Rgb(200, 0, 200),
+        camera: Color::Rgb(0, 120, 150),
+        camera_view: Color::Rgb(30, 70, 170),
+    };
+
+    /// Pure black and white with bright colors, for low vision or washed out screens.
+    pub const HIGH_CONTRAST: Self = Self {
+        text: Color::White,
+        muted: Color::Gray,
+        label: Color::White,
+        title: Color::LightRed,
+        heading: Color::LightCyan,
+        accent: Color::LightGreen,
+        highlight: Color::LightYellow,
+        warning: Color::LightRed,
+        error: Color::LightRed,
+        row: Color::Black,
+        row_alt: Color::Rgb(20, 20, 20),
+        row_selected: Color::Blue,
+        row_marked: Color::Rgb(0, 80, 80),
+        header: Color::Rgb(0, 0, 120),
+        popup: Color::Black,
+        field: Color::Rgb(40, 40, 40),
+        field_focused: Color::White,
+        field_focused_text: Color::Black,
+        selection: Color::LightYellow,
+        selection_text: Color::Black,
+        swatch: Color::Black,
+        swatch_invalid: Color::LightMagenta,
+        camera: Color::LightCyan,
+        camera_view: Color::LightMagenta,
+    };
+
+    /// Loads `theme.json` from the config dir. A missing file gives the dark theme, but one that
+    /// can't be read or has unknown colors is an error.
+    pub fn load() -> Result<Self> {
+        let Some(path) = config_dir().map(|dir| dir.join("theme.json")) else {
+            return Ok(Self::default());
+        };
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let text = fs::read_to_string(&path)
+            .wrap_err_with(|| format!("couldn't read {}", path.display()))?;
+        Self::parse(&text).wrap_err_with(|| format!("bad theme in {}", path.display()))
+    }
+
+    fn parse(text: &str) -> Result<Self> {
+        let config: serde_json::Value = serde_json::from_str(text)?;
+        let entries = config
+            .as_object()
+            .ok_or_else(|| eyre!("expected an object of color names to colors"))?;
+
+        let mut theme = match entries.get("base") {
+            None => Self::default(),
+            Some(base) => {
+                let base = base
+                    .as_str()
+                    .ok_or_else(|| eyre!("`base` should be the name of a theme"))?;
+                THEMES
+                    .iter()
+                    .find(|(name, _)| *name == base)
+                    .map(|(_, theme)| *theme)
+                    .ok_or_else(|| eyre!("unknown theme `{base}`"))?
+            }
+        };
+        for (name, value) in entries.iter().filter(|(name, _)| *name != "base") {
+            let slot = theme
+                .slot(name)
+                .ok_or_else(|| eyre!("unknown color `{name}`"))?;
+            let value = value
+                .as_str()
+                .ok_or_else(|| eyre!("`{name}` should be a color"))?;
+            *slot = Color::from_str(value)
+                .map_err(|_| eyre!("`{value}` for `{name}` isn't a color"))?;
+        }
+        Ok(theme)
+    }
+
+    /// The color with the name the theme file uses for it.
+    fn slot(&mut self, name: &str) -> Option<&mut Color> {
+        Some(match name {
+            "text" => &mut self.text,
+            "muted" => &mut self.muted,
+            "label" => &mut self.label,
+            "title" => &mut self.title,
+            "heading" => &mut self.heading,
+            "accent" => &mut self.accent,
+            "highlight" => &mut self.highlight,
+            "warning" => &mut self.warning,
+            "error" => &mut self.error,
+            "row" => &mut self.row,
+            "row_alt" => &mut self.row_alt,
+            "row_selected" => &mut self.row_selected,
+            "row_marked" => &mut self.row_marked,
+            "header" => &mut self.header,
+            "popup" => &mut self.popup,
+            "field" => &mut self.field,
+            "field_focused" => &mut self.field_focused,
+            "field_focused_text" => &mut self.field_focused_text,
+            "selection" => &mut self.selection,
+            "selection_text" => &mut self.selection_text,
+            "swatch" => &mut self.swatch,
+            "swatch_invalid" => &mut self.swatch_invalid,
+            "camera" => &mut self.camera,
+            "camera_view" => &mut self.camera_view,
+            _ => return None,
+        })
+    }
+}
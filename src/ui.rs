@@ -2,13 +2,24 @@
 use std::default;
 
 use crate::{
-    app::SkyType,
+    app::{shown, SkyType},
+    form::{
+        form, render_row, FieldKind, FormStyle, ARRAY_FORM, BULK_FORM, GROUP_FORM, MATERIAL_FORM,
+        OBJECT_FORM, RENAME_FORM, SKY_FORM,
+    },
+    group::TableRow,
+    inspector::render_inspector,
+    keys::Section,
+    map::render_map,
+    picker::render_picker,
+    preview::render_split_preview,
     render::{self, render_view},
+    viewer::render_viewer,
     render_preview, App, CurrentScreen, CurrentlyEditing, MaterialType,
 };
 use color_eyre::owo_colors::OwoColorize;
 use ratatui::{
-    layout::{Alignment, Constraint, Direction, Layout, Margin, Rect},
+    layout::{Alignment, Constraint, Direction, Layout, Margin, Position, Rect},
     style::{Color, Modifier, Style, Styled, Stylize},
     symbols::line::TOP_RIGHT,
     text::{Line, Span, Text},
@@ -22,6 +33,63 @@ use ratatui::{
 use rtwlib::color::Color as RColor;
 use rtwlib::material::Material;
 
+/// Lines taken up by the object table's header and each of its rows
+const TABLE_HEADER_HEIGHT: u16 = 2;
+const TABLE_ROW_HEIGHT: u16 = 1;
+/// Lines taken up by the inspector under the object table, borders included
+const INSPECTOR_HEIGHT: u16 = 7;
+
+/// Sidebar help for getting around the current screen's form. It's worked out from the fields
+/// that are showing and the key bindings, so it says what the keys actually do.
+fn form_help(app: &App) -> Vec<Line<'static>> {
+    let fields: Vec<_> = form(&app.current_screen)
+        .iter()
+        .flat_map(|row| row.iter())
+        .filter(|field| (field.visible)(app))
+        .collect();
+    let numbers = fields.iter().any(|field| field.is_stepped(app));
+    let colors = fields
+        .iter()
+        .any(|field| matches!(field.kind, FieldKind::Color { .. }));
+    let choices: Vec<String> = fields
+        .iter()
+        .filter(|field| matches!(field.kind, FieldKind::Choice { .. }))
+        .map(|field| field.label(app).to_lowercase())
+        .collect();
+
+    let mut lines = Vec::new();
+    if fields.len() > 1 {
+        lines.push(Line::from("  Tab & Shift+Tab: Change inputs"));
+        lines.push(Line::from(
+            "  ← & →: Move cursor, change inputs at the ends",
+        ));
+    }
+    if fields.len() > choices.len() {
+        lines.push(Line::from("  Home/End, Ctrl+W: Jump, delete word"));
+    }
+    if colors {
+        lines.push(Line::from("  Type colors as hex, like ff8800"));
+        lines.extend(app.keys.help(Section::Editor).into_iter().map(Line::from));
+    }
+    let mut arrows = Vec::new();
+    if numbers {
+        lines.push(Line::from("  Shift+↑/↓ or scroll: Fine step numbers"));
+        arrows.push(String::from("Step numbers"));
+    }
+    if !choices.is_empty() {
+        let verb = if arrows.is_empty() {
+            "Change"
+        } else {
+            "change"
+        };
+        arrows.push(format!("{verb} {}", choices.join(", ")));
+    }
+    if !arrows.is_empty() {
+        lines.push(Line::from(format!("  ↑ & ↓: {}", arrows.join(", "))));
+    }
+    lines
+}
+
 /// helper function to create a centered rect using up certain percentage of the available rect `r`
 pub fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
     // Cut the given rectangle into three vertical pieces
@@ -66,10 +134,28 @@ pub fn ui(frame: &mut Frame, app: &App) {
         .direction(Direction::Vertical)
         .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
         .split(outer[0]);
+    // with the split preview on, it takes the right half of the main screen
+    let (work, split) = if app.split_preview {
+        let halves = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+            .split(outer[1]);
+        (halves[0], Some(halves[1]))
+    } else {
+        (outer[1], None)
+    };
     let main = Layout::default()
         .direction(Direction::Vertical)
         .constraints([Constraint::Min(3), Constraint::Length(3)])
-        .split(outer[1]);
+        .split(work);
+    // popups go in the middle of the screen, or over the table when they'd cover the preview
+    let popup_column = if split.is_some() {
+        work
+    } else {
+        centered_rect(50, 100, frame.area())
+    };
+
+    let theme = &app.theme;
 
     //the info on the sidebar
     let info_block = Block::default()
@@ -78,14 +164,14 @@ pub fn ui(frame: &mut Frame, app: &App) {
         .style(Style::default());
 
     let mut info_lines = vec![
-        Line::styled("╦═╗╔╦╗╦ ╦ ┬─┐┌─┐", Style::default().fg(Color::Red)),
-        Line::styled("╠╦╝ ║ ║║║ ├┬┘└─┐", Style::default().fg(Color::Red)),
-        Line::styled("╩╚═ ╩ ╚╩╝°┴└─└─┘", Style::default().fg(Color::Red)),
-        Line::styled("Raytracing in rust", Style::default().fg(Color::Green)),
+        Line::styled("╦═╗╔╦╗╦ ╦ ┬─┐┌─┐", Style::default().fg(theme.title)),
+        Line::styled("╠╦╝ ║ ║║║ ├┬┘└─┐", Style::default().fg(theme.title)),
+        Line::styled("╩╚═ ╩ ╚╩╝°┴└─└─┘", Style::default().fg(theme.title)),
+        Line::styled("Raytracing in rust", Style::default().fg(theme.accent)),
         Line::styled(
             "CONTROLS",
             Style::default()
-                .fg(Color::LightBlue)
+                .fg(theme.heading)
                 .add_modifier(Modifier::BOLD),
         ),
     ];
@@ -95,95 +181,79 @@ pub fn ui(frame: &mut Frame, app: &App) {
             info_lines.push(Line::styled(
                 "Main Page:",
                 Style::default()
-                    .fg(Color::Blue)
+                    .fg(theme.heading)
                     .add_modifier(Modifier::BOLD),
             ));
-            info_lines.push(Line::styled(
-                "  ↑ & ↓: Scroll object list",
-                Style::default(),
-            ));
-            info_lines.push(Line::styled(
-                "  [D]: Delete selected object",
-                Style::default(),
-            ));
-            info_lines.push(Line::styled("  [N]: Create a new object", Style::default()));
-            info_lines.push(Line::styled(
-                "  [M]: Create a new material",
-                Style::default(),
-            ));
-            info_lines.push(Line::styled(
-                "  [P]: View a preview render (ESC to close)",
-                Style::default(),
-            ));
-            info_lines.push(Line::styled(
-                "  [B]: Edit the sky/background",
-                Style::default(),
-            ));
-
-            info_lines.push(Line::styled("  [R]: Render the scene", Style::default()));
-            info_lines.push(Line::styled("  [Q]: Quit", Style::default()));
+            info_lines.extend(app.keys.help(Section::Main).into_iter().map(Line::from));
         }
         CurrentScreen::Editor => {
-            info_lines.push(Line::styled("Editor:", Style::default().fg(Color::Green)));
+            info_lines.push(Line::styled("Editor:", Style::default().fg(theme.accent)));
+            info_lines.extend(form_help(app));
+            info_lines.push(Line::from("  Enter: Save"));
+            info_lines.push(Line::from("  Esc: Cancel"));
+        }
+        CurrentScreen::ArrayEditor => {
+            info_lines.push(Line::styled("Array:", Style::default().fg(theme.accent)));
+            info_lines.extend(form_help(app));
+            info_lines.push(Line::from("  Enter: Make copies"));
+            info_lines.push(Line::from("  Esc: Cancel"));
+        }
+        CurrentScreen::GroupEditor => {
+            info_lines.push(Line::styled("Group:", Style::default().fg(theme.accent)));
+            info_lines.extend(form_help(app));
+            info_lines.push(Line::from("  Enter: Save"));
+            info_lines.push(Line::from("  Esc: Cancel"));
+        }
+        CurrentScreen::BulkEditor => {
             info_lines.push(Line::styled(
-                "  Tab & Shift+Tab: Change inputs",
-                Style::default(),
+                "Edit Selection:",
+                Style::default().fg(theme.accent),
             ));
-            info_lines.push(Line::styled("  ← & →: Change inputs", Style::default()));
-            info_lines.push(Line::styled("  Type to input", Style::default()));
-            info_lines.push(Line::styled("  ↑ & ↓: Choose Material", Style::default()));
-            info_lines.push(Line::styled("  Enter: Save", Style::default()));
-            info_lines.push(Line::styled("  Esc: Cancel", Style::default()));
+            info_lines.extend(form_help(app));
+            info_lines.push(Line::from("  Enter: Apply"));
+            info_lines.push(Line::from("  Esc: Cancel"));
+        }
+        CurrentScreen::Rename => {
+            info_lines.push(Line::styled("Rename:", Style::default().fg(theme.accent)));
+            info_lines.push(Line::from("  Type a name, empty for the default"));
+            info_lines.extend(form_help(app));
+            info_lines.push(Line::from("  Enter: Save"));
+            info_lines.push(Line::from("  Esc: Cancel"));
         }
         CurrentScreen::MaterialEditor => {
             info_lines.push(Line::styled(
                 "Material Editor:",
-                Style::default().fg(Color::Green),
-            ));
-            info_lines.push(Line::styled(
-                "  Tab & Shift+Tab: Change inputs",
-                Style::default(),
+                Style::default().fg(theme.accent),
             ));
-            info_lines.push(Line::styled("  ← & →: Change inputs", Style::default()));
-            info_lines.push(Line::styled("  Type to input color", Style::default()));
-            info_lines.push(Line::styled(
-                "  ↑ & ↓: cycle through material types",
-                Style::default(),
-            ));
-            info_lines.push(Line::styled("  Enter: Save", Style::default()));
-            info_lines.push(Line::styled("  Esc: Cancel", Style::default()));
+            info_lines.extend(form_help(app));
+            info_lines.push(Line::from("  Enter: Save"));
+            info_lines.push(Line::from("  Esc: Cancel"));
         }
         CurrentScreen::Render => {
             info_lines.push(Line::styled(
                 "Render settings",
-                Style::default().fg(Color::Red),
+                Style::default().fg(theme.title),
             ));
-            info_lines.push("  Tab & Shift+Tab: Change inputs".into());
-            info_lines.push(Line::styled("  ← & →: Change inputs", Style::default()));
-            info_lines.push("  Type to input".into());
+            info_lines.extend(form_help(app));
+            info_lines.extend(app.keys.help(Section::Render).into_iter().map(Line::from));
             info_lines.push("  Enter: Render scene ( this might take a bit )".into());
             info_lines.push("  Esc: Close".into());
         }
         CurrentScreen::Preview => {
-            info_lines.push(Line::styled("Preview", Style::default().fg(Color::Red)));
-            info_lines.push("  [F]: Full Screen".into());
+            info_lines.push(Line::styled("Preview", Style::default().fg(theme.title)));
+            info_lines.extend(app.keys.help(Section::Preview).into_iter().map(Line::from));
+            info_lines.push("  Esc: Close".into());
+        }
+        CurrentScreen::Viewer => {
+            info_lines.push(Line::styled("Viewer", Style::default().fg(theme.title)));
+            info_lines.extend(app.keys.help(Section::Viewer).into_iter().map(Line::from));
             info_lines.push("  Esc: Close".into());
         }
         CurrentScreen::SkyEditor => {
-            info_lines.push(Line::styled("Sky Editor", Style::default().fg(Color::Red)));
-            info_lines.push(Line::styled(
-                "  Tab & Shift+Tab: Change inputs",
-                Style::default(),
-            ));
-            info_lines.push(Line::styled("  ← & →: Change inputs", Style::default()));
-            info_lines.push(Line::styled("  Type to input color", Style::default()));
-            info_lines.push(Line::styled(
-                "  ↑ & ↓: cycle through material types",
-                Style::default(),
-            ));
-            info_lines.push(Line::styled("  Type to input", Style::default()));
-            info_lines.push(Line::styled("  Enter: Save", Style::default()));
-            info_lines.push(Line::styled("  Esc: Cancel", Style::default()));
+            info_lines.push(Line::styled("Sky Editor", Style::default().fg(theme.title)));
+            info_lines.extend(form_help(app));
+            info_lines.push(Line::from("  Enter: Save"));
+            info_lines.push(Line::from("  Esc: Cancel"));
         }
         _ => {}
     }
@@ -193,50 +263,107 @@ pub fn ui(frame: &mut Frame, app: &App) {
         .wrap(Wrap { trim: false });
 
     //object table
-    let object_data = app.world.as_info_vec();
-
     let object_block = Block::default()
         .borders(Borders::ALL)
         .border_type(BorderType::Rounded);
 
-    let rows = object_data
-        .iter()
+    let shown = shown(&app.objects);
+    let cursor = app.cursor();
+    let rows = app
+        .table_rows()
+        .into_iter()
         .enumerate()
-        .map(|(i, data)| {
-            let color: Color;
-            if Some(i) == app.selected_object {
-                color = Color::Rgb(45, 45, 55);
+        .map(|(position, (row, depth))| {
+            let marked = match row {
+                TableRow::Object(i) => app.marked.contains(&i),
+                TableRow::Group(_) => false,
+            };
+            let color = if Some(row) == cursor {
+                theme.row_selected
+            } else if marked {
+                theme.row_marked
+            } else if position % 2 == 0 {
+                theme.row
             } else {
-                color = match i % 2 {
-                    0 => Color::Rgb(30, 30, 40),
-                    _ => Color::Rgb(25, 25, 35),
-                };
-            }
-            let item = data
-                .iter()
-                .map(|content| Cell::from(Text::from(format!("\n{content}\n"))))
-                .collect::<Vec<_>>();
-            Row::new(item)
-                .style(Style::default().fg(Color::White).bg(color))
-                .height(3)
+                theme.row_alt
+            };
+            // rows are indented under the group they're in
+            let indent = "  ".repeat(depth);
+            let cells = match row {
+                TableRow::Group(g) => {
+                    let group = &app.groups[g];
+                    let arrow = if group.collapsed { "▸" } else { "▾" };
+                    let p = group.pivot + group.offset;
+                    vec![
+                        String::new(),
+                        format!("{indent}{arrow} {}", group.label(g)),
+                        String::from("Group"),
+                        format!("{:.2}, {:.2}, {:.2}", p.x, p.y, p.z),
+                        format!("x{:.2}", group.scale),
+                        format!("{} objects", app.group_members(g).len()),
+                    ]
+                }
+                TableRow::Object(i) => {
+                    let object = &app.objects[i];
+                    let p = object.position;
+                    let material = app
+                        .materials
+                        .get(object.material)
+                        .map_or("?", |material| material.name.as_str());
+                    let state = if object.solo {
+                        " (solo)"
+                    } else if object.hidden {
+                        " (hidden)"
+                    } else {
+                        ""
+                    };
+                    vec![
+                        if marked {
+                            format!("●{i}")
+                        } else {
+                            i.to_string()
+                        },
+                        format!("{indent}{}{state}", object.label(i)),
+                        object.kind.to_string(),
+                        format!("{:.2}, {:.2}, {:.2}", p.x, p.y, p.z),
+                        format!("{:.2}", object.size),
+                        material.to_string(),
+                    ]
+                }
+            };
+            // objects left out of the render are grayed out
+            let text = match row {
+                TableRow::Object(i) if !shown.contains(&i) => theme.muted,
+                _ => theme.text,
+            };
+            Row::new(cells)
+                .style(Style::default().fg(text).bg(color))
+                .height(TABLE_ROW_HEIGHT)
         })
         .collect::<Vec<_>>();
 
     let widths = [
-        Constraint::Length(8),
-        Constraint::Length(6),
-        Constraint::Length(5),
-        Constraint::Length(5),
-        Constraint::Length(5),
+        Constraint::Length(4),
+        Constraint::Min(12),
+        Constraint::Length(7),
+        Constraint::Min(18),
+        Constraint::Length(7),
         Constraint::Min(10),
     ];
     let mut table_state = TableState::default();
-    table_state.select(app.selected_object);
+    table_state.select(app.cursor_position());
     let table = Table::new(rows, widths)
         .header(
-            Row::new(vec!["Type", "Size", "X", "Y", "Z", "Material"])
-                .style(Style::default().bg(Color::Rgb(30, 40, 75)))
-                .height(2),
+            Row::new(vec![
+                "#",
+                "Name",
+                "Type",
+                "Center / Normal",
+                "Size",
+                "Material",
+            ])
+            .style(Style::default().fg(theme.text).bg(theme.header))
+            .height(TABLE_HEADER_HEIGHT),
         )
         .block(object_block);
 
@@ -245,8 +372,8 @@ pub fn ui(frame: &mut Frame, app: &App) {
 
     for material in app.materials.iter() {
         materials.push(ListItem::new(Line::from(Span::styled(
-            material.0.as_str(),
-            Style::default().fg(Color::LightYellow),
+            material.name.as_str(),
+            Style::default().fg(theme.highlight),
         ))));
     }
     let material_block = Block::default()
@@ -266,25 +393,24 @@ pub fn ui(frame: &mut Frame, app: &App) {
         .style(Style::default());
 
     let stats_lines = vec![Line::from(vec![
-        Span::raw(app.world.objects.len().to_string()),
-        Span::styled(" Objects in Scene. ", Style::default().fg(Color::Green)),
+        Span::raw(app.objects.len().to_string()),
+        Span::styled(" Objects in Scene. ", Style::default().fg(theme.accent)),
         Span::raw(app.materials.len().to_string()),
-        Span::styled(" Materials.", Style::default().fg(Color::LightBlue)),
-        Span::styled("| ", Style::default().fg(Color::LightRed)),
+        Span::styled(" Materials.", Style::default().fg(theme.heading)),
+        Span::styled("| ", Style::default().fg(theme.muted)),
         Span::raw(app.samples.to_string()),
-        Span::styled(" Samples. ", Style::default().fg(Color::Cyan)),
+        Span::styled(" Samples. ", Style::default().fg(theme.accent)),
         Span::raw(app.bounces.to_string()),
-        Span::styled(" Bounces. ", Style::default().fg(Color::Magenta)),
-        Span::styled("|", Style::default().fg(Color::LightRed)),
+        Span::styled(" Bounces. ", Style::default().fg(theme.heading)),
+        Span::styled("|", Style::default().fg(theme.muted)),
         Span::styled(
             format!(" {}.ppm ", app.image_name_input),
-            Style::default().fg(Color::LightYellow),
+            Style::default().fg(theme.highlight),
         ),
-        Span::raw(format!(
-            "{}x{}",
-            app.image_width.to_string(),
-            app.image_height.to_string()
-        )),
+        Span::raw(match app.render_size() {
+            Ok((width, height)) => format!("{width}x{height}"),
+            Err(_) => format!("{}x{}", app.image_width, app.image_height),
+        }),
     ])];
 
     let stats = Paragraph::new(Text::from(stats_lines))
@@ -295,315 +421,249 @@ pub fn ui(frame: &mut Frame, app: &App) {
         .title("Quit")
         .title_bottom("[Y / N]")
         .title_alignment(Alignment::Center)
-        .title_style(Style::default().fg(Color::Red).add_modifier(Modifier::BOLD))
+        .title_style(
+            Style::default()
+                .fg(theme.title)
+                .add_modifier(Modifier::BOLD),
+        )
         .borders(Borders::ALL)
         .border_type(BorderType::Double);
     let confirmation_text = Text::styled(
-        "Are you SURE you want to quit? The current scene will not be saved, and all your changes will be lost", Style::default().fg(Color::LightRed)
+        "Are you SURE you want to quit? The current scene will not be saved, and all your changes will be lost", Style::default().fg(theme.warning)
     );
     let confirmation_paragraph = Paragraph::new(confirmation_text)
         .block(confirmation_block)
         .wrap(Wrap { trim: false });
 
     frame.render_widget(info, outer[0]);
-    frame.render_widget(
-        material_list,
-        sidebar[1].inner(Margin {
+    let sidebar_bottom = sidebar[1].inner(Margin {
+        vertical: 1,
+        horizontal: 1,
+    });
+    if app.show_map {
+        render_map(frame, sidebar_bottom, app);
+    } else {
+        frame.render_widget(material_list, sidebar_bottom);
+    }
+    frame.render_widget(stats, main[1]);
+    // the selected object's details go under the table
+    let objects_area = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(5), Constraint::Length(INSPECTOR_HEIGHT)])
+        .split(main[0]);
+    frame.render_stateful_widget(table, objects_area[0], &mut table_state);
+    app.table_area.set(objects_area[0]);
+    app.table_offset.set(table_state.offset());
+    let mut scrollbar_state = ScrollbarState::new(app.table_rows().len())
+        .position(app.cursor_position().unwrap_or(table_state.offset()));
+    frame.render_stateful_widget(
+        Scrollbar::default(),
+        objects_area[0].inner(Margin {
             vertical: 1,
-            horizontal: 1,
+            horizontal: 0,
         }),
+        &mut scrollbar_state,
     );
-    frame.render_widget(stats, main[1]);
-    frame.render_stateful_widget(table, main[0], &mut table_state);
+    render_inspector(frame, objects_area[1], app);
+
+    let editing = matches!(
+        app.current_screen,
+        CurrentScreen::Main
+            | CurrentScreen::Editor
+            | CurrentScreen::MaterialEditor
+            | CurrentScreen::Render
+            | CurrentScreen::SkyEditor
+            | CurrentScreen::ArrayEditor
+            | CurrentScreen::Rename
+            | CurrentScreen::BulkEditor
+            | CurrentScreen::GroupEditor
+    );
+    if let Some(area) = split.filter(|_| editing) {
+        render_split_preview(frame, area, app).unwrap_or(());
+    }
 
     match app.current_screen {
         CurrentScreen::Confirmation => {
             frame.render_widget(Clear, frame.area());
             frame.render_widget(confirmation_paragraph, centered_rect(40, 20, frame.area()))
         }
-        CurrentScreen::Editor => editor(frame, app),
-        CurrentScreen::MaterialEditor => material_editor(frame, app),
+        CurrentScreen::Editor => editor(frame, app, popup_column),
+        CurrentScreen::MaterialEditor => material_editor(frame, app, popup_column),
         CurrentScreen::Render => render_view(frame, main[0], app),
         CurrentScreen::Preview => render_preview(frame, main[0], app, true).unwrap_or(()),
         CurrentScreen::PreviewFull => render_preview(frame, frame.area(), app, false).unwrap_or(()),
-        CurrentScreen::SkyEditor => sky_editor(frame, app),
+        CurrentScreen::SkyEditor => sky_editor(frame, app, popup_column),
+        CurrentScreen::ArrayEditor => array_editor(frame, app, popup_column),
+        CurrentScreen::Rename => rename_editor(frame, app, popup_column),
+        CurrentScreen::BulkEditor => bulk_editor(frame, app, popup_column),
+        CurrentScreen::GroupEditor => group_editor(frame, app, popup_column),
+        CurrentScreen::Viewer => render_viewer(frame, main[0], app),
         _ => {}
     }
+    render_picker(frame, main[0], app);
 }
 
-fn editor(frame: &mut Frame, app: &App) {
+/// The object or group in the table row under a terminal cell, if there is one.
+pub fn table_row_at(app: &App, column: u16, row: u16) -> Option<TableRow> {
+    let area = app.table_area.get();
+    // skip the border and the header
+    let top = area.y + 1 + TABLE_HEADER_HEIGHT;
+    if !area.contains(Position::new(column, row)) || row < top {
+        return None;
+    }
+    let index = app.table_offset.get() + usize::from((row - top) / TABLE_ROW_HEIGHT);
+    app.table_rows().get(index).map(|&(row, _)| row)
+}
+
+fn editor(frame: &mut Frame, app: &App, column: Rect) {
     //Editor popup
     let editor_block = Block::default()
         .title("Create a new object")
-        .title_style(Style::default().fg(Color::Red))
+        .title_style(Style::default().fg(app.theme.title))
         .borders(Borders::ALL)
-        .style(Style::default().bg(Color::Black))
+        .style(Style::default().bg(app.theme.popup))
         .border_type(BorderType::Rounded);
-    let editor_area = centered_rect(50, 20, frame.area());
-    let editor_chunks = Layout::default()
-        .direction(Direction::Horizontal)
-        .margin(((editor_area.height - 2) / 2).clamp(1, 5))
-        .spacing(2)
-        .constraints([
-            Constraint::Min(6),
-            Constraint::Min(6),
-            Constraint::Min(4),
-            Constraint::Min(4),
-            Constraint::Min(4),
-            Constraint::Min(16),
-        ])
-        .split(editor_area);
-
-    let mut bl_type = Block::default()
-        .title("Type")
-        .borders(Borders::NONE)
-        .bg(Color::DarkGray);
-    let mut bl_radius = Block::default()
-        .title(match app.type_input {
-            0 => "Radius",
-            1 => "Y",
-            _ => "ERROR",
-        })
-        .borders(Borders::NONE)
-        .bg(Color::DarkGray);
-    let mut bl_posx = Block::default()
-        .title(match app.type_input {
-            0 => "X",
-            1 => "Facing X",
-            _ => "ERROR",
-        })
-        .borders(Borders::NONE)
-        .bg(Color::DarkGray);
-    let mut bl_posy = Block::default()
-        .title(match app.type_input {
-            0 => "Y",
-            1 => "Facing Y",
-            _ => "ERROR",
-        })
-        .borders(Borders::NONE)
-        .bg(Color::DarkGray);
-    let mut bl_posz = Block::default()
-        .title(match app.type_input {
-            0 => "Z",
-            1 => "Facing Z",
-            _ => "ERROR",
-        })
-        .borders(Borders::NONE)
-        .bg(Color::DarkGray);
-    let mut bl_mat = Block::default()
-        .title("Material")
-        .borders(Borders::NONE)
-        .bg(Color::DarkGray);
-
-    let selected_style = Style::default().bg(Color::White).fg(Color::Black);
-
-    if let Some(editing) = &app.current_edit {
-        match editing {
-            CurrentlyEditing::Type => bl_type = bl_type.style(selected_style),
-            CurrentlyEditing::Size => bl_radius = bl_radius.style(selected_style),
-            CurrentlyEditing::PositionX => bl_posx = bl_posx.style(selected_style),
-            CurrentlyEditing::PositionY => bl_posy = bl_posy.style(selected_style),
-            CurrentlyEditing::PositionZ => bl_posz = bl_posz.style(selected_style),
-            CurrentlyEditing::Material => bl_mat = bl_mat.style(selected_style),
-            _ => {}
-        }
-    }
-    let txt_type = Paragraph::new(app.get_type()).block(bl_type);
-    let txt_size = Paragraph::new(app.size_input.clone()).block(bl_radius);
-    let txt_posx = Paragraph::new(app.position_input_x.clone()).block(bl_posx);
-    let txt_posy = Paragraph::new(app.position_input_y.clone()).block(bl_posy);
-    let txt_posz = Paragraph::new(app.position_input_z.clone()).block(bl_posz);
-    let txt_mat = Paragraph::new(app.materials[app.material_input].0.clone()).block(bl_mat);
+    let editor_area = centered_rect(100, 20, column);
+    let margin = (editor_area.height.saturating_sub(2) / 2).clamp(1, 5);
 
     frame.render_widget(Clear, editor_area);
     frame.render_widget(editor_block, editor_area);
-
-    frame.render_widget(txt_type, editor_chunks[0]);
-    frame.render_widget(txt_size, editor_chunks[1]);
-    frame.render_widget(txt_posx, editor_chunks[2]);
-    frame.render_widget(txt_posy, editor_chunks[3]);
-    frame.render_widget(txt_posz, editor_chunks[4]);
-    frame.render_widget(txt_mat, editor_chunks[5]);
+    render_row(
+        frame,
+        editor_area.inner(Margin::new(margin, margin)),
+        app,
+        OBJECT_FORM[0],
+        FormStyle::Flat,
+    );
 }
 
-fn material_editor(frame: &mut Frame, app: &App) {
+fn material_editor(frame: &mut Frame, app: &App, column: Rect) {
     let editor_block = Block::default()
         .title("Create a new material")
         .borders(Borders::ALL)
         .style(Style::default())
         .border_type(BorderType::Rounded);
-    let editor_area = centered_rect(50, 25, frame.area());
-    let editor_chunks = Layout::default()
-        .direction(Direction::Horizontal)
-        .margin(2)
-        .spacing(2)
-        .constraints([
-            Constraint::Min(7),  //Type
-            Constraint::Min(7),  //Color
-            Constraint::Min(5),  //other
-            Constraint::Min(15), //name
-        ])
-        .split(editor_area);
-    let mut bl_type = Block::default()
-        .title("Type")
-        .borders(Borders::NONE)
-        .bg(Color::DarkGray);
-    let mut bl_color = Block::default()
-        .title("Color")
-        .borders(Borders::NONE)
-        .bg(Color::Rgb(
-            (app.get_color().x * 255.0) as u8,
-            (app.get_color().y * 255.0) as u8,
-            (app.get_color().z * 255.0) as u8,
-        ))
-        .fg(Color::Rgb(
-            255 - (app.get_color().x * 255.0) as u8,
-            255 - (app.get_color().y * 255.0) as u8,
-            255 - (app.get_color().z * 255.0) as u8,
-        ));
-    let mut bl_other = Block::default()
-        .title(match app.mat_type_input {
-            Some(MaterialType::Metal) => "Roughness",
-            Some(MaterialType::Dielectric) => "IOR",
-            _ => "ERROR",
-        })
-        .borders(Borders::NONE)
-        .bg(Color::DarkGray);
-    let mut bl_name = Block::default()
-        .title("Name")
-        .borders(Borders::NONE)
-        .bg(Color::DarkGray);
-
-    let selected_style = Style::default().bg(Color::White).fg(Color::Black);
-
-    if let Some(editing) = &app.current_edit {
-        match editing {
-            CurrentlyEditing::MatType => bl_type = bl_type.style(selected_style),
-            CurrentlyEditing::MatColor => bl_color = bl_color.style(selected_style),
-            CurrentlyEditing::MatProperty => bl_other = bl_other.style(selected_style),
-            CurrentlyEditing::MatName => bl_name = bl_name.style(selected_style),
-            _ => {}
-        }
-    }
+    let editor_area = centered_rect(100, 25, column);
 
-    let txt_type = Paragraph::new(app.mat_type_input.clone().unwrap().to_string()).block(bl_type);
-    let txt_color = Paragraph::new(app.mat_color_input.clone()).block(bl_color);
-    let txt_other = Paragraph::new(app.mat_other_input.clone()).block(bl_other);
-    let txt_name = Paragraph::new(app.mat_name_input.clone()).block(bl_name);
     frame.render_widget(Clear, editor_area);
     frame.render_widget(editor_block, editor_area);
-
-    frame.render_widget(txt_type, editor_chunks[0]);
-    frame.render_widget(txt_color, editor_chunks[1]);
-    match app.mat_type_input {
-        Some(MaterialType::Metal) => {
-            frame.render_widget(txt_other, editor_chunks[2]);
-        }
-        Some(MaterialType::Dielectric) => {
-            frame.render_widget(txt_other, editor_chunks[2]);
-        }
-        _ => {}
-    }
-    frame.render_widget(txt_name, editor_chunks[3]);
+    render_row(
+        frame,
+        editor_area.inner(Margin::new(2, 2)),
+        app,
+        MATERIAL_FORM[0],
+        FormStyle::Flat,
+    );
 }
 
-fn sky_editor(frame: &mut Frame, app: &App) {
+fn sky_editor(frame: &mut Frame, app: &App, column: Rect) {
     let editor_block = Block::default()
         .title("Editing Sky")
         .borders(Borders::ALL)
         .style(Style::default())
         .border_type(BorderType::Rounded);
-    let editor_area = centered_rect(50, 25, frame.area());
-    let editor_chunks = Layout::default()
-        .direction(Direction::Horizontal)
-        .margin(2)
-        .spacing(2)
-        .constraints([
-            Constraint::Min(8), //Type
-            Constraint::Min(7), //Color
-            Constraint::Min(7), //other
-        ])
-        .split(editor_area);
-    let mut bl_type = Block::default()
-        .title("Type")
-        .borders(Borders::NONE)
-        .bg(Color::DarkGray);
-
-    let selcol1: [u8; 3];
-    if app.sky_color1.len() == 6 {
-        selcol1 = match RColor::from_hex(&app.sky_color1) {
-            Ok(color) => color.to_rgb_bytes(),
-            Err(_) => RColor::new(1.0, 0.0, 1.0).to_rgb_bytes(),
-        };
-    } else {
-        selcol1 = RColor::new(1.0, 0.0, 1.0).to_rgb_bytes();
-    }
-    let selcol2: [u8; 3];
-    if app.sky_color2.len() == 6 {
-        selcol2 = match RColor::from_hex(&app.sky_color2) {
-            Ok(color) => color.to_rgb_bytes(),
-            Err(_) => RColor::new(1.0, 0.0, 1.0).to_rgb_bytes(),
-        };
-    } else {
-        selcol2 = RColor::new(1.0, 0.0, 1.0).to_rgb_bytes();
+    let editor_area = centered_rect(100, 25, column);
+
+    frame.render_widget(Clear, editor_area);
+    frame.render_widget(editor_block, editor_area);
+    render_row(
+        frame,
+        editor_area.inner(Margin::new(2, 2)),
+        app,
+        SKY_FORM[0],
+        FormStyle::Flat,
+    );
+}
+
+fn array_editor(frame: &mut Frame, app: &App, column: Rect) {
+    let editor_block = Block::default()
+        .title(format!("Copy {}", selected_label(app)))
+        .borders(Borders::ALL)
+        .style(Style::default())
+        .border_type(BorderType::Rounded);
+    let editor_area = centered_rect(100, 30, column);
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(2), Constraint::Length(2)])
+        .spacing(1)
+        .split(editor_area.inner(Margin::new(2, 2)));
+
+    frame.render_widget(Clear, editor_area);
+    frame.render_widget(editor_block, editor_area);
+    for (row, area) in ARRAY_FORM.iter().zip(rows.iter()) {
+        render_row(frame, *area, app, row, FormStyle::Flat);
     }
-    let mut bl_color1 = Block::default()
-        .title(match app.sky_type {
-            SkyType::Gradient => "Top Color",
-            SkyType::Solid => "Color",
-        })
-        .borders(Borders::NONE)
-        .bg(Color::Black)
-        .fg(Color::Rgb(selcol1[0], selcol1[1], selcol1[2]));
-    let mut bl_color2 = Block::default()
-        .title("Bottom Color")
-        .borders(Borders::NONE)
-        .bg(Color::Black)
-        .fg(Color::Rgb(selcol2[0], selcol2[1], selcol2[2]));
-
-    let selected_style = Style::default().bg(Color::White).fg(Color::Black);
-    let inverted1 = Style::default()
-        .bg(Color::Rgb(selcol1[0], selcol1[1], selcol1[2]))
-        .fg(Color::Rgb(
-            255 - selcol1[0],
-            255 - selcol1[1],
-            255 - selcol1[2],
-        ));
-    let inverted2 = Style::default()
-        .bg(Color::Rgb(selcol2[0], selcol2[1], selcol2[2]))
-        .fg(Color::Rgb(
-            255 - selcol2[0],
-            255 - selcol2[1],
-            255 - selcol2[2],
-        ));
+}
 
-    if let Some(editing) = &app.current_edit {
-        match editing {
-            CurrentlyEditing::SkyType => bl_type = bl_type.style(selected_style),
-            CurrentlyEditing::SkyColor1 => bl_color1 = bl_color1.style(inverted1),
-            CurrentlyEditing::SkyColor2 => bl_color2 = bl_color2.style(inverted2),
-            _ => {}
-        }
+/// The name of the object or group under the cursor, for popup titles.
+fn selected_label(app: &App) -> String {
+    match app.cursor() {
+        Some(TableRow::Object(i)) => app.objects[i].label(i),
+        Some(TableRow::Group(g)) => app.groups[g].label(g),
+        None => String::new(),
     }
+}
 
-    let txt_type = Paragraph::new(match app.sky_type {
-        SkyType::Gradient => "Gradient",
-        SkyType::Solid => "Solid",
-    })
-    .block(bl_type);
+fn rename_editor(frame: &mut Frame, app: &App, column: Rect) {
+    let editor_block = Block::default()
+        .title(format!("Rename {}", selected_label(app)))
+        .borders(Borders::ALL)
+        .style(Style::default())
+        .border_type(BorderType::Rounded);
+    let editor_area = centered_rect(60, 20, column);
+
+    frame.render_widget(Clear, editor_area);
+    frame.render_widget(editor_block, editor_area);
+    render_row(
+        frame,
+        editor_area.inner(Margin::new(2, 1)),
+        app,
+        RENAME_FORM[0],
+        FormStyle::Flat,
+    );
+}
 
-    let txt_color1 = Paragraph::new(app.sky_color1.clone()).block(bl_color1);
-    let txt_color2 = Paragraph::new(app.sky_color2.clone()).block(bl_color2);
+fn bulk_editor(frame: &mut Frame, app: &App, column: Rect) {
+    let title = match app.targets().as_slice() {
+        [] => String::from("Edit objects"),
+        &[i] => format!("Edit {}", app.objects[i].label(i)),
+        targets => format!("Edit {} objects", targets.len()),
+    };
+    let editor_block = Block::default()
+        .title(title)
+        .borders(Borders::ALL)
+        .style(Style::default())
+        .border_type(BorderType::Rounded);
+    let editor_area = centered_rect(100, 20, column);
+    let margin = (editor_area.height.saturating_sub(2) / 2).clamp(1, 5);
 
     frame.render_widget(Clear, editor_area);
     frame.render_widget(editor_block, editor_area);
+    render_row(
+        frame,
+        editor_area.inner(Margin::new(2, margin)),
+        app,
+        BULK_FORM[0],
+        FormStyle::Flat,
+    );
+}
 
-    frame.render_widget(txt_type, editor_chunks[0]);
-    frame.render_widget(txt_color1, editor_chunks[1]);
-    match app.sky_type {
-        SkyType::Gradient => {
-            frame.render_widget(txt_color2, editor_chunks[2]);
-        }
-        _ => {}
+fn group_editor(frame: &mut Frame, app: &App, column: Rect) {
+    let editor_block = Block::default()
+        .title(format!("Edit {}", selected_label(app)))
+        .borders(Borders::ALL)
+        .style(Style::default())
+        .border_type(BorderType::Rounded);
+    let editor_area = centered_rect(100, 30, column);
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(2), Constraint::Length(2)])
+        .spacing(1)
+        .split(editor_area.inner(Margin::new(2, 2)));
+
+    frame.render_widget(Clear, editor_area);
+    frame.render_widget(editor_block, editor_area);
+    for (row, area) in GROUP_FORM.iter().zip(rows.iter()) {
+        render_row(frame, *area, app, row, FormStyle::Flat);
     }
 }
@@ -0,0 +1,201 @@
+#![warn(clippy::pedantic)]
+
+use std::fs;
+
+use color_eyre::{eyre::eyre, Result};
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    widgets::{Block, BorderType, Borders, Widget},
+    Frame,
+};
+
+use crate::{preview::draw_half_blocks, App};
+
+/// Shown around the image when it doesn't fill the view
+const BACKDROP: [u8; 3] = [18, 18, 24];
+
+/// A binary (P6) PPM image, the format renders are saved in.
+pub struct Image {
+    pub width: usize,
+    pub height: usize,
+    /// RGB bytes, row by row
+    pixels: Vec<u8>,
+}
+
+impl Image {
+    pub fn load_ppm(path: &str) -> Result<Self> {
+        let data = fs::read(path)?;
+        let mut pos = 0;
+        let mut header = Vec::new();
+        // magic number, width, height and max value, separated by whitespace and # comments
+        while header.len() < 4 {
+            match data.get(pos) {
+                Some(b'#') => {
+                    while data.get(pos).is_some_and(|&c| c != b'\n') {
+                        pos += 1;
+                    }
+                }
+                Some(c) if c.is_ascii_whitespace() => pos += 1,
+                Some(_) => {
+                    let start = pos;
+                    while data.get(pos).is_some_and(|c| !c.is_ascii_whitespace()) {
+                        pos += 1;
+                    }
+                    header.push(String::from_utf8_lossy(&data[start..pos]).to_string());
+                }
+                None => return Err(eyre!("{path} ends before the end of the header")),
+            }
+        }
+        if header[0] != "P6" {
+            return Err(eyre!("{path} is not a binary PPM file"));
+        }
+        let width: usize = header[1].parse()?;
+        let height: usize = header[2].parse()?;
+        if header[3] != "255" {
+            return Err(eyre!("only 8 bit PPM files are supported"));
+        }
+        // a single whitespace byte separates the header from the pixels
+        let pixels = data
+            .get(pos + 1..pos + 1 + width * height * 3)
+            .ok_or_else(|| eyre!("{path} is missing pixel data"))?
+            .to_vec();
+        Ok(Self {
+            width,
+            height,
+            pixels,
+        })
+    }
+
+    fn pixel(&self, x: usize, y: usize) -> [u8; 3] {
+        let i = (y * self.width + x) * 3;
+        [self.pixels[i], self.pixels[i + 1], self.pixels[i + 2]]
+    }
+
+    /// Averages the pixels in the given box, clipped to the image.
+    #[allow(
+        clippy::cast_possible_truncation,
+        clippy::cast_sign_loss,
+        clippy::cast_precision_loss
+    )]
+    fn sample(&self, x0: f64, y0: f64, x1: f64, y1: f64) -> Option<[u8; 3]> {
+        let (w, h) = (self.width as f64, self.height as f64);
+        if x1 <= 0.0 || y1 <= 0.0 || x0 >= w || y0 >= h {
+            return None;
+        }
+        let (left, top) = (x0.max(0.0) as usize, y0.max(0.0) as usize);
+        // always cover at least one pixel, so zooming in doesn't leave gaps
+        let right = (x1.min(w).ceil() as usize).max(left + 1);
+        let bottom = (y1.min(h).ceil() as usize).max(top + 1);
+
+        let mut total = [0u32; 3];
+        for y in top..bottom {
+            for x in left..right {
+                let pixel = self.pixel(x, y);
+                for c in 0..3 {
+                    total[c] += u32::from(pixel[c]);
+                }
+            }
+        }
+        let count = ((right - left) * (bottom - top)) as u32;
+        Some(total.map(|c| (c / count) as u8))
+    }
+}
+
+/// An opened render, and where in it the view is looking.
+pub struct Viewer {
+    pub path: String,
+    pub image: Image,
+    /// 1.0 fits the whole image in the view
+    pub zoom: f64,
+    /// The point in the image, in pixels, shown at the middle of the view
+    pub center: (f64, f64),
+    pub fullscreen: bool,
+}
+
+impl Viewer {
+    #[allow(clippy::cast_precision_loss)]
+    pub fn open(path: String) -> Result<Self> {
+        let image = Image::load_ppm(&path)?;
+        let center = (image.width as f64 / 2.0, image.height as f64 / 2.0);
+        Ok(Self {
+            path,
+            image,
+            zoom: 1.0,
+            center,
+            fullscreen: false,
+        })
+    }
+
+    pub fn zoom_by(&mut self, factor: f64) {
+        self.zoom = (self.zoom * factor).clamp(0.25, 64.0);
+    }
+
+    /// Moves the view by a fraction of what's currently visible.
+    #[allow(clippy::cast_precision_loss)]
+    pub fn pan(&mut self, dx: f64, dy: f64) {
+        let (w, h) = (self.image.width as f64, self.image.height as f64);
+        self.center.0 = (self.center.0 + dx * w / self.zoom).clamp(0.0, w);
+        self.center.1 = (self.center.1 + dy * h / self.zoom).clamp(0.0, h);
+    }
+
+    #[allow(clippy::cast_precision_loss)]
+    pub fn reset(&mut self) {
+        self.zoom = 1.0;
+        self.center = (self.image.width as f64 / 2.0, self.image.height as f64 / 2.0);
+    }
+}
+
+pub fn render_viewer(frame: &mut Frame, area: Rect, app: &App) {
+    let Some(viewer) = &app.viewer else {
+        return;
+    };
+    let area = if viewer.fullscreen {
+        frame.area()
+    } else {
+        area
+    };
+    let block = Block::new()
+        .title(format!("{} ({:.0}%)", viewer.path, viewer.zoom * 100.0))
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded);
+    frame.render_widget(ImageView { viewer, block }, area);
+}
+
+struct ImageView<'a> {
+    viewer: &'a Viewer,
+    block: Block<'a>,
+}
+
+impl Widget for ImageView<'_> {
+    #[allow(clippy::cast_precision_loss)]
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let inner = self.block.inner(area);
+        self.block.render(area, buf);
+        let image = &self.viewer.image;
+        if image.width == 0 || image.height == 0 {
+            return;
+        }
+
+        // view size in pixels, each cell is two pixels tall
+        let (view_w, view_h) = (f64::from(inner.width), f64::from(inner.height) * 2.0);
+        let fit = (view_w / image.width as f64).min(view_h / image.height as f64);
+        let scale = fit * self.viewer.zoom;
+        let (cx, cy) = self.viewer.center;
+
+        let lines: Vec<Vec<[u8; 3]>> = (0..inner.height * 2)
+            .map(|sy| {
+                let y0 = cy + (f64::from(sy) - view_h / 2.0) / scale;
+                (0..inner.width)
+                    .map(|sx| {
+                        let x0 = cx + (f64::from(sx) - view_w / 2.0) / scale;
+                        image
+                            .sample(x0, y0, x0 + 1.0 / scale, y0 + 1.0 / scale)
+                            .unwrap_or(BACKDROP)
+                    })
+                    .collect()
+            })
+            .collect();
+        draw_half_blocks(inner, buf, &lines);
+    }
+}
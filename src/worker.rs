@@ -0,0 +1,62 @@
+#![warn(clippy::pedantic)]
+//! Traces the split-screen preview on a background thread, so editing doesn't wait on it.
+
+use std::{
+    sync::mpsc::{channel, Receiver, Sender},
+    thread,
+};
+
+use crate::preview::{PreviewCache, PreviewJob};
+
+/// Runs one preview job at a time. Jobs requested while one is running wait, and only the newest
+/// waiting job is kept, so typing quickly doesn't pile up work.
+pub struct PreviewWorker {
+    sender: Sender<PreviewCache>,
+    results: Receiver<PreviewCache>,
+    /// Key of the job being traced
+    running: Option<u64>,
+    queued: Option<(u64, PreviewJob)>,
+}
+
+impl Default for PreviewWorker {
+    fn default() -> Self {
+        let (sender, results) = channel();
+        Self {
+            sender,
+            results,
+            running: None,
+            queued: None,
+        }
+    }
+}
+
+impl PreviewWorker {
+    pub fn request(&mut self, key: u64, job: PreviewJob) {
+        if self.running == Some(key) {
+            self.queued = None;
+        } else if self.running.is_some() {
+            self.queued = Some((key, job));
+        } else {
+            self.start(key, job);
+        }
+    }
+
+    fn start(&mut self, key: u64, job: PreviewJob) {
+        let sender = self.sender.clone();
+        self.running = Some(key);
+        thread::spawn(move || {
+            // the receiver only goes away when the app is closing
+            let _ = sender.send(job.run(key));
+        });
+    }
+
+    /// Returns the finished preview if the running job is done, and starts the queued job.
+    pub fn poll(&mut self) -> Option<PreviewCache> {
+        let finished = self.results.try_recv().ok()?;
+        self.running = None;
+        if let Some((key, job)) = self.queued.take() {
+            self.start(key, job);
+        }
+        Some(finished)
+    }
+}